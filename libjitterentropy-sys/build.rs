@@ -2,19 +2,73 @@ use std::{env::var, path::PathBuf};
 
 use bindgen::Builder;
 
+/// Compiles the jitterentropy-library sources, returns the directory of its headers.
+///
+/// The sources are expected in `jitterentropy-library` next to this file, or in the
+/// directory given by `JITTERENTROPY_SOURCE_DIR`. Architecture specific code is chosen
+/// by the library headers based on the target, so all C files are compiled.
+#[cfg(feature = "vendored")]
+fn build_vendored() -> PathBuf {
+    println!("cargo:rerun-if-env-changed=JITTERENTROPY_SOURCE_DIR");
+    let source_dir = var("JITTERENTROPY_SOURCE_DIR").map_or_else(
+        |_| PathBuf::from(var("CARGO_MANIFEST_DIR").unwrap()).join("jitterentropy-library"),
+        PathBuf::from,
+    );
+
+    let sources: Vec<PathBuf> = std::fs::read_dir(source_dir.join("src"))
+        .unwrap_or_else(|e| {
+            panic!(
+                "Cannot read jitterentropy-library sources in {}: {e}",
+                source_dir.display()
+            )
+        })
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "c"))
+        .collect();
+    for source in &sources {
+        println!("cargo:rerun-if-changed={}", source.display());
+    }
+
+    let mut build = cc::Build::new();
+    build
+        .files(&sources)
+        .include(&source_dir)
+        .include(source_dir.join("src"))
+        // upstream requires -O0: the compiler must not optimize away or reorder the
+        // memory accesses and loops whose timing is the entropy source, its Makefile
+        // and CMake build enforce it for the same reason
+        .opt_level(0)
+        .flag_if_supported("-fwrapv")
+        .flag_if_supported("-fvisibility=hidden")
+        .define("JENT_CONF_ENABLE_INTERNAL_TIMER", None);
+    // selects the SHA3 of libcrypto, like upstream's EXTERNAL_CRYPTO=OPENSSL
+    #[cfg(feature = "openssl-sha3")]
+    build.define("OPENSSL", None);
+    build.compile("jitterentropy");
+    println!("cargo:rustc-link-lib=pthread");
+
+    source_dir
+}
+
 fn main() {
-    #[cfg(feature = "openssl")]
+    let builder = Builder::default()
+        .header("jitterentropy-include.h")
+        .use_core();
+    #[cfg(feature = "vendored")]
+    let builder = builder.clang_arg(format!("-I{}", build_vendored().display()));
+    #[cfg(not(feature = "vendored"))]
+    println!("cargo:rustc-link-lib=jitterentropy");
+
+    // `internal-sha3` needs nothing, it is what jitterentropy uses without `OPENSSL`.
+    // libcrypto has to follow jitterentropy on the linker command line, so that a static
+    // jitterentropy resolves its SHA3 symbols.
+    #[cfg(feature = "openssl-sha3")]
     pkg_config::Config::new().probe("libcrypto").unwrap();
 
-    let bindings = Builder::default()
-        .header("jitterentropy-include.h")
-        .generate()
-        .unwrap();
+    let bindings = builder.generate().unwrap();
     let mut bindings_path = PathBuf::from(var("OUT_DIR").unwrap());
     bindings_path.push("jitterentropy-bindings.rs");
     bindings
         .write_to_file(&bindings_path)
         .expect("Could not write bindings to file");
-
-    println!("cargo:rustc-link-lib=jitterentropy");
 }
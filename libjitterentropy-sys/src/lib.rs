@@ -1 +1,9 @@
+#![cfg_attr(not(test), no_std)]
+
+#[cfg(all(feature = "internal-sha3", feature = "openssl-sha3"))]
+compile_error!(
+    "features `internal-sha3` and `openssl-sha3` are mutually exclusive, disable default features to use `openssl-sha3`"
+);
+
 pub mod jitterentropy;
+pub mod safe;
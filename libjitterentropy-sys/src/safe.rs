@@ -0,0 +1,251 @@
+//! Thin checked wrappers around the most common jitterentropy calls.
+//!
+//! Errors are reported as the raw C error codes of jitterentropy.
+
+use crate::jitterentropy::{
+    jent_entropy_collector_alloc, jent_entropy_collector_free, jent_entropy_init_ex,
+    jent_fips_failure_cb, jent_read_entropy, jent_read_entropy_safe,
+    jent_set_fips_failure_callback, jent_version, rand_data,
+};
+
+/// Error code of jitterentropy for a NULL entropy collector.
+pub const ERR_NULL_COLLECTOR: i32 = -1;
+
+/// Error code of jitterentropy for programming or internal errors.
+pub const ERR_PROG: i32 = 7;
+
+/// Runs the global jitterentropy self-tests with oversampling rate `osr` and `flags`.
+///
+/// # Errors
+///
+/// Returns the non-zero error code of `jent_entropy_init_ex`.
+pub fn entropy_init_ex(osr: u32, flags: u32) -> Result<(), i32> {
+    match unsafe { jent_entropy_init_ex(osr, flags) } {
+        0 => Ok(()),
+        code => Err(code),
+    }
+}
+
+/// Allocates a new entropy collector.
+///
+/// The returned collector has to be released with `entropy_collector_free`.
+///
+/// # Errors
+///
+/// Returns `ERR_NULL_COLLECTOR` if the allocation failed.
+pub fn entropy_collector_alloc(osr: u32, flags: u32) -> Result<*mut rand_data, i32> {
+    let ec = unsafe { jent_entropy_collector_alloc(osr, flags) };
+    if ec.is_null() {
+        Err(ERR_NULL_COLLECTOR)
+    } else {
+        Ok(ec)
+    }
+}
+
+/// Fills `dst` from the entropy collector `ec`.
+///
+/// On intermittent health test failures jitterentropy may replace the collector by one
+/// with a higher oversampling rate, so `ec` may point to a different collector
+/// afterwards. Permanent failures are returned without replacing it.
+///
+/// # Errors
+///
+/// Returns `ERR_NULL_COLLECTOR` if `ec` is NULL, `ERR_PROG` if the length of `dst` cannot
+/// be represented or the read was incomplete, and the negative error code of
+/// `jent_read_entropy_safe` otherwise.
+///
+/// # Safety
+///
+/// `ec` must point to a collector allocated by `entropy_collector_alloc`, which is not
+/// used concurrently.
+pub unsafe fn read_entropy_safe(ec: &mut *mut rand_data, dst: &mut [u8]) -> Result<(), i32> {
+    if ec.is_null() {
+        return Err(ERR_NULL_COLLECTOR);
+    }
+    let expected_len = isize::try_from(dst.len()).map_err(|_| ERR_PROG)?;
+
+    let ret = unsafe { jent_read_entropy_safe(ec, dst.as_mut_ptr().cast(), dst.len()) };
+
+    if ret == expected_len {
+        Ok(())
+    } else if ret < 0 {
+        Err(i32::try_from(ret).unwrap_or(ERR_PROG))
+    } else {
+        Err(ERR_PROG)
+    }
+}
+
+/// Fills `dst` from the entropy collector `ec` without any recovery.
+///
+/// Unlike `read_entropy_safe`, the collector is never replaced on health test failures.
+/// Returns the number of bytes produced.
+///
+/// # Errors
+///
+/// Returns `ERR_NULL_COLLECTOR` if `ec` is NULL, `ERR_PROG` if the length of `dst` cannot
+/// be represented and the negative error code of `jent_read_entropy` otherwise.
+///
+/// # Safety
+///
+/// `ec` must point to a collector allocated by `entropy_collector_alloc`, which is not
+/// used concurrently.
+pub unsafe fn read_entropy(ec: *mut rand_data, dst: &mut [u8]) -> Result<usize, i32> {
+    if ec.is_null() {
+        return Err(ERR_NULL_COLLECTOR);
+    }
+    isize::try_from(dst.len()).map_err(|_| ERR_PROG)?;
+
+    let ret = unsafe { jent_read_entropy(ec, dst.as_mut_ptr().cast(), dst.len()) };
+
+    usize::try_from(ret).map_err(|_| i32::try_from(ret).unwrap_or(ERR_PROG))
+}
+
+/// Health test state of an entropy collector, see `health_counters`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct HealthCounters {
+    /// Repetition count test: number of consecutive stuck values
+    pub rct_count: i32,
+    /// Adaptive proportion test: occurrences of the base value in the current window
+    pub apt_count: u32,
+    /// Adaptive proportion test: observations collected in the current window
+    pub apt_observations: u32,
+    /// Health failure state of the collector, 0 if all tests pass
+    pub health_failure: u32,
+}
+
+/// Reads the health test state of the entropy collector `ec`.
+///
+/// Returns `None` if `ec` is NULL.
+///
+/// # Safety
+///
+/// `ec` must be NULL or point to a collector allocated by `entropy_collector_alloc`,
+/// which is not used concurrently.
+#[must_use]
+pub unsafe fn health_counters(ec: *const rand_data) -> Option<HealthCounters> {
+    let ec = unsafe { ec.as_ref() }?;
+
+    Some(HealthCounters {
+        rct_count: ec.rct_count,
+        apt_count: ec.apt_count,
+        apt_observations: ec.apt_observations,
+        health_failure: ec.health_failure,
+    })
+}
+
+/// Releases the entropy collector `ec`, NULL is ignored.
+///
+/// # Safety
+///
+/// `ec` must be NULL or point to a collector allocated by `entropy_collector_alloc`,
+/// which is not used afterwards.
+pub unsafe fn entropy_collector_free(ec: *mut rand_data) {
+    if !ec.is_null() {
+        unsafe { jent_entropy_collector_free(ec) };
+    }
+}
+
+/// Registers `cb`, which jitterentropy invokes on permanent health test failures.
+///
+/// The registration is global for all collectors of the process.
+///
+/// # Errors
+///
+/// Returns the non-zero error code of `jent_set_fips_failure_callback`.
+pub fn set_fips_failure_callback(cb: jent_fips_failure_cb) -> Result<(), i32> {
+    match unsafe { jent_set_fips_failure_callback(cb) } {
+        0 => Ok(()),
+        code => Err(code),
+    }
+}
+
+/// Returns the version of the linked jitterentropy library.
+///
+/// The version is encoded as `major * 1_000_000 + minor * 10_000 + patchlevel * 100`.
+#[must_use]
+pub fn version() -> u32 {
+    unsafe { jent_version() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::jitterentropy::JENT_FORCE_FIPS;
+
+    #[test]
+    fn test_init() {
+        assert_eq!(entropy_init_ex(3, JENT_FORCE_FIPS), Ok(()));
+    }
+
+    #[test]
+    fn test_alloc_read_free() {
+        entropy_init_ex(3, JENT_FORCE_FIPS).unwrap();
+        let mut ec = entropy_collector_alloc(3, JENT_FORCE_FIPS).unwrap();
+
+        let mut buf = [0u8; 64];
+        assert_eq!(unsafe { read_entropy_safe(&mut ec, &mut buf) }, Ok(()));
+        assert_eq!(unsafe { read_entropy_safe(&mut ec, &mut []) }, Ok(()));
+        assert_eq!(unsafe { read_entropy(ec, &mut buf) }, Ok(buf.len()));
+        let counters = unsafe { health_counters(ec) }.unwrap();
+        assert_eq!(counters.health_failure, 0);
+
+        unsafe { entropy_collector_free(ec) };
+    }
+
+    /// Size of `struct rand_data` as `(major.minor version, pointer width, size)`.
+    ///
+    /// Recorded for the library versions the bindings were verified against, see README.
+    /// 3.6 is the release pinned for vendored builds.
+    const RAND_DATA_SIZES: &[(u32, usize, usize)] = &[(306, 64, 232)];
+
+    #[test]
+    fn test_version() {
+        assert!(version() >= 3_000_000);
+    }
+
+    #[test]
+    fn test_rand_data_layout() {
+        let major_minor = version() / 10_000;
+        let pointer_width = core::mem::size_of::<usize>() * 8;
+        let size = core::mem::size_of::<crate::jitterentropy::rand_data>();
+
+        let Some(&(_, _, expected)) = RAND_DATA_SIZES
+            .iter()
+            .find(|&&(v, width, _)| v == major_minor && width == pointer_width)
+        else {
+            let missing = format!(
+                "no recorded size of rand_data for jitterentropy {}.{} on {pointer_width} bit, \
+                 bindings report {size} bytes",
+                major_minor / 100,
+                major_minor % 100
+            );
+            // vendored builds compile a pinned release, whose size has to be recorded
+            assert!(!cfg!(feature = "vendored"), "{missing}");
+            println!("Skipping test: {missing}");
+            return;
+        };
+        assert_eq!(
+            size,
+            expected,
+            "rand_data layout of the bindings does not match jitterentropy {}.{}",
+            major_minor / 100,
+            major_minor % 100
+        );
+    }
+
+    #[test]
+    fn test_null_collector() {
+        let mut ec = core::ptr::null_mut();
+        let mut buf = [0u8; 8];
+        assert_eq!(
+            unsafe { read_entropy_safe(&mut ec, &mut buf) },
+            Err(ERR_NULL_COLLECTOR)
+        );
+        assert_eq!(
+            unsafe { read_entropy(ec, &mut buf) },
+            Err(ERR_NULL_COLLECTOR)
+        );
+        assert_eq!(unsafe { health_counters(ec) }, None);
+        unsafe { entropy_collector_free(ec) };
+    }
+}
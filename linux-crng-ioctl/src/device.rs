@@ -0,0 +1,519 @@
+use crate::error::CrngError;
+use crate::ioctl::CreditReport;
+use crate::ioctl_defs;
+use log::{debug, error};
+use std::fs::{File, OpenOptions};
+use std::os::fd::AsRawFd;
+use std::path::Path;
+
+/// Operations on the kernel entropy pool.
+///
+/// Abstracts the kernel interface, so consumers can be tested with a mock instead of
+/// touching `/dev/random`.
+pub trait KernelEntropy {
+    /// Gets the current entropy count in bits, see `ioctl::get_ent_cnt`.
+    ///
+    /// # Errors
+    ///
+    /// Returns error if the entropy count cannot be read.
+    fn get_ent_cnt(&self) -> Result<i32, CrngError>;
+
+    /// Adds to the entropy count, see `ioctl::add_to_ent_cnt`.
+    ///
+    /// # Errors
+    ///
+    /// Returns error if the entropy count cannot be modified.
+    fn add_to_ent_cnt(&self, ent_cnt: i32) -> Result<(), CrngError>;
+
+    /// Adds `entropy` to the input pool claiming `ent_bits`, see `ioctl::add_randomness_to_kernel`.
+    ///
+    /// # Errors
+    ///
+    /// Returns error if the data cannot be added.
+    fn add_randomness(&self, entropy: &[u8], ent_bits: u32) -> Result<(), CrngError>;
+
+    /// Mixes `data` into the input pool without crediting any entropy, see
+    /// `ioctl::write_without_crediting`. Returns the number of bytes written.
+    ///
+    /// # Errors
+    ///
+    /// Returns error if the write fails.
+    fn write_without_crediting(&self, data: &[u8]) -> Result<usize, CrngError>;
+
+    /// Clears the entropy count, see `ioctl::clear_entropy_count`.
+    ///
+    /// # Errors
+    ///
+    /// Returns error if the entropy count cannot be cleared.
+    fn clear_entropy_count(&self) -> Result<(), CrngError>;
+
+    /// Clears the input pool, see `ioctl::clear_pool`.
+    ///
+    /// # Errors
+    ///
+    /// Returns error if the pool cannot be cleared.
+    fn clear_pool(&self) -> Result<(), CrngError>;
+
+    /// Forces a reseed of the CRNG, see `ioctl::force_kernel_crng_reseed`.
+    ///
+    /// # Errors
+    ///
+    /// Returns error if the CRNG cannot be reseeded.
+    fn reseed(&self) -> Result<(), CrngError>;
+}
+
+/// Character devices of the kernel RNG.
+///
+/// Both nodes share the same ioctl implementation in the kernel, so every operation of
+/// `CrngDevice` works on either of them. Reading the entropy count is unprivileged, all
+/// other ioctls require `CAP_SYS_ADMIN` regardless of the node.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RandomDevice {
+    /// `/dev/random`
+    #[default]
+    Random,
+    /// `/dev/urandom`
+    Urandom,
+}
+
+impl RandomDevice {
+    /// Path of the device node.
+    #[must_use]
+    pub fn path(self) -> &'static Path {
+        match self {
+            Self::Random => Path::new("/dev/random"),
+            Self::Urandom => Path::new("/dev/urandom"),
+        }
+    }
+}
+
+/// Open handle of a kernel random device, `/dev/random` by default.
+///
+/// Keeps the device open for all operations, instead of opening it for every call like
+/// the free functions of the `ioctl` module.
+#[derive(Debug)]
+pub struct CrngDevice {
+    file: File,
+}
+
+impl CrngDevice {
+    /// Opens `/dev/random`.
+    ///
+    /// # Errors
+    ///
+    /// Returns error if `/dev/random` cannot be opened for writing.
+    pub fn open() -> Result<Self, CrngError> {
+        Self::open_device(RandomDevice::Random)
+    }
+
+    /// Opens the kernel random device `device`.
+    ///
+    /// # Errors
+    ///
+    /// Returns error if the device cannot be opened for writing.
+    pub fn open_device(device: RandomDevice) -> Result<Self, CrngError> {
+        Self::open_path(device.path())
+    }
+
+    /// Opens the random device node at `path`, e.g. inside a chroot or container.
+    ///
+    /// # Errors
+    ///
+    /// Returns error if `path` cannot be opened for writing.
+    pub fn open_path(path: &Path) -> Result<Self, CrngError> {
+        Ok(CrngDevice {
+            file: OpenOptions::new()
+                .write(true)
+                .open(path)
+                .map_err(CrngError::DeviceOpen)?,
+        })
+    }
+
+    /// Gets the current entropy count in bits, see `ioctl::get_ent_cnt`.
+    ///
+    /// # Errors
+    ///
+    /// Returns error if the ioctl call fails.
+    pub fn get_ent_cnt(&self) -> Result<i32, CrngError> {
+        let mut ent_cnt = 0;
+
+        let ret = unsafe { ioctl_defs::rnd_get_ent_cnt(self.file.as_raw_fd(), &mut ent_cnt) };
+        check_ioctl(ret)?;
+        Ok(ent_cnt)
+    }
+
+    /// Adds to (or subtracts from) the entropy count, see `ioctl::add_to_ent_cnt`.
+    ///
+    /// # Errors
+    ///
+    /// Returns error if the ioctl call fails, e.g. without root privileges.
+    pub fn add_to_ent_cnt(&self, ent_cnt: i32) -> Result<(), CrngError> {
+        let ret = unsafe { ioctl_defs::rnd_add_to_ent_cnt(self.file.as_raw_fd(), &ent_cnt) };
+        check_ioctl(ret)
+    }
+
+    /// Adds `entropy` to the input pool claiming `ent_bits`, see
+    /// `ioctl::add_randomness_to_kernel`.
+    ///
+    /// # Errors
+    ///
+    /// Returns error if `ent_bits` exceeds the bits of `entropy`, `entropy` exceeds
+    /// `MAX_BUFFER_SIZE` or the ioctl call fails, e.g. without root privileges.
+    pub fn add_randomness(&self, entropy: &[u8], ent_bits: u32) -> Result<(), CrngError> {
+        // the kernel takes the claim as a signed int
+        let entropy_bits = i32::try_from(ent_bits).map_err(|_| CrngError::EntropyOverclaim {
+            ent_bits,
+            max_bits: u64::from(i32::MAX.unsigned_abs()),
+        })?;
+        check_overclaim(entropy, ent_bits)?;
+
+        if entropy.len() > ioctl_defs::MAX_BUFFER_SIZE {
+            return Err(CrngError::BufferTooLarge);
+        }
+
+        debug!(
+            "Write {} Byte to kernel input pool, accounted with {} Bit entropy",
+            entropy.len(),
+            ent_bits
+        );
+
+        let mut pool_info = ioctl_defs::KernelRandPoolInfo {
+            header: ioctl_defs::KernelRandPoolInfoHeader {
+                entropy_bits,
+                buf_size_byte: i32::try_from(entropy.len())
+                    .map_err(|_| CrngError::BufferTooLarge)?,
+            },
+            buf: [0; ioctl_defs::MAX_BUFFER_SIZE],
+        };
+        pool_info.buf[0..entropy.len()].copy_from_slice(entropy);
+
+        #[allow(clippy::ptr_as_ptr)]
+        let res = unsafe {
+            ioctl_defs::rnd_add_entropy(
+                self.file.as_raw_fd(),
+                std::ptr::addr_of!(pool_info) as *const ioctl_defs::KernelRandPoolInfoHeader,
+            )
+        };
+
+        check_ioctl(res)
+    }
+
+    /// Mixes `data` into the input pool without crediting any entropy, see
+    /// `ioctl::write_without_crediting`.
+    ///
+    /// # Errors
+    ///
+    /// Returns error if the write fails.
+    pub fn write_without_crediting(&self, data: &[u8]) -> Result<usize, CrngError> {
+        let written = nix::unistd::write(&self.file, data)?;
+        debug!("Wrote {written} Byte to kernel input pool without entropy credit");
+        Ok(written)
+    }
+
+    /// Adds `entropy` of any size to the input pool claiming `ent_bits`, see
+    /// `ioctl::add_randomness_to_kernel_chunked`.
+    ///
+    /// # Errors
+    ///
+    /// Returns error if `ent_bits` exceeds the bits of `entropy` or any chunk cannot be
+    /// added. Chunks before the failing one stay in the pool.
+    pub fn add_randomness_chunked(&self, entropy: &[u8], ent_bits: u32) -> Result<(), CrngError> {
+        check_overclaim(entropy, ent_bits)?;
+
+        let mut start = 0;
+        for chunk in entropy.chunks(ioctl_defs::MAX_BUFFER_SIZE) {
+            let end = start + chunk.len();
+            let bits = chunk_bits(entropy.len(), start, end, ent_bits);
+            self.add_randomness(chunk, bits)?;
+            start = end;
+        }
+
+        Ok(())
+    }
+
+    /// Adds `entropy` to the input pool and reports the effective credit, see
+    /// `ioctl::add_randomness_to_kernel_counted`.
+    ///
+    /// # Errors
+    ///
+    /// Returns all errors of `get_ent_cnt` and `add_randomness`.
+    pub fn add_randomness_counted(
+        &self,
+        entropy: &[u8],
+        ent_bits: u32,
+    ) -> Result<CreditReport, CrngError> {
+        let before = self.get_ent_cnt()?;
+        self.add_randomness(entropy, ent_bits)?;
+        let after = self.get_ent_cnt()?;
+
+        Ok(CreditReport {
+            bytes_written: entropy.len(),
+            requested_bits: ent_bits,
+            credited_bits: after - before,
+        })
+    }
+
+    /// Clears the entropy count to zero, see `ioctl::clear_entropy_count`.
+    ///
+    /// # Errors
+    ///
+    /// Returns error if the ioctl call fails, e.g. without root privileges.
+    pub fn clear_entropy_count(&self) -> Result<(), CrngError> {
+        check_ioctl(unsafe { ioctl_defs::rnd_zap_ent_cnt(self.file.as_raw_fd()) })?;
+        debug!("Cleared kernel CRNG entropy count to 0");
+        Ok(())
+    }
+
+    /// Clears the input pool and associated counters, see `ioctl::clear_pool`.
+    ///
+    /// # Errors
+    ///
+    /// Returns error if the ioctl call fails, e.g. without root privileges.
+    pub fn clear_pool(&self) -> Result<(), CrngError> {
+        check_ioctl(unsafe { ioctl_defs::rnd_clear_pool(self.file.as_raw_fd()) })?;
+        debug!("Forcefully cleared kernel CRNG pool");
+        Ok(())
+    }
+
+    /// Forces a reseed of the CRNG, see `ioctl::force_kernel_crng_reseed`.
+    ///
+    /// # Errors
+    ///
+    /// Returns error if the ioctl call fails, e.g. without root privileges.
+    pub fn reseed(&self) -> Result<(), CrngError> {
+        check_ioctl(unsafe { ioctl_defs::rnd_reseed_crng(self.file.as_raw_fd()) })?;
+        debug!("Forcefully reseeded kernel CRNG");
+        Ok(())
+    }
+}
+
+/// Maps the result of an ioctl call, which returns 0 on success.
+fn check_ioctl(ret: nix::Result<i32>) -> Result<(), CrngError> {
+    match ret {
+        Ok(0) => Ok(()),
+        Ok(_) => {
+            error!("ioctl returned with error");
+            Err(CrngError::Ioctl(nix::errno::Errno::UnknownErrno))
+        }
+        Err(errno) => {
+            error!("ioctl returned with error {errno}");
+            Err(CrngError::from_ioctl(errno))
+        }
+    }
+}
+
+/// Number of bits in a buffer of `len` bytes, saturating instead of overflowing.
+fn buffer_bits(len: usize) -> u64 {
+    u64::try_from(len)
+        .ok()
+        .and_then(|len| len.checked_mul(8))
+        .unwrap_or(u64::MAX)
+}
+
+/// Fails if `ent_bits` exceeds the bits of `entropy`.
+fn check_overclaim(entropy: &[u8], ent_bits: u32) -> Result<(), CrngError> {
+    let max_bits = buffer_bits(entropy.len());
+    if u64::from(ent_bits) <= max_bits {
+        Ok(())
+    } else {
+        Err(CrngError::EntropyOverclaim { ent_bits, max_bits })
+    }
+}
+
+/// Share of `ent_bits` claimed for bytes `start..end` of a buffer of `total` bytes.
+///
+/// The shares of adjacent ranges add up exactly to `ent_bits`, rounding never
+/// claims more entropy in total.
+fn chunk_bits(total: usize, start: usize, end: usize, ent_bits: u32) -> u32 {
+    // usize always fits into u128
+    let claimed_until = |pos: usize| u128::from(ent_bits) * pos as u128 / total as u128;
+
+    // the difference never exceeds `ent_bits`
+    u32::try_from(claimed_until(end) - claimed_until(start)).unwrap_or(0)
+}
+
+impl KernelEntropy for CrngDevice {
+    fn get_ent_cnt(&self) -> Result<i32, CrngError> {
+        Self::get_ent_cnt(self)
+    }
+
+    fn add_to_ent_cnt(&self, ent_cnt: i32) -> Result<(), CrngError> {
+        Self::add_to_ent_cnt(self, ent_cnt)
+    }
+
+    fn add_randomness(&self, entropy: &[u8], ent_bits: u32) -> Result<(), CrngError> {
+        Self::add_randomness(self, entropy, ent_bits)
+    }
+
+    fn write_without_crediting(&self, data: &[u8]) -> Result<usize, CrngError> {
+        Self::write_without_crediting(self, data)
+    }
+
+    fn clear_entropy_count(&self) -> Result<(), CrngError> {
+        Self::clear_entropy_count(self)
+    }
+
+    fn clear_pool(&self) -> Result<(), CrngError> {
+        Self::clear_pool(self)
+    }
+
+    fn reseed(&self) -> Result<(), CrngError> {
+        Self::reseed(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nix::unistd::Uid;
+
+    #[test]
+    fn test_crng_device() {
+        let device = CrngDevice::open().unwrap();
+        let first = device.get_ent_cnt().unwrap();
+        let second = device.get_ent_cnt().unwrap();
+        assert!(first >= 0 && second >= 0);
+
+        let device: &dyn KernelEntropy = &device;
+        assert!(device.get_ent_cnt().is_ok(), "failed to get entropy count");
+    }
+
+    #[test]
+    fn test_urandom_device() {
+        let device = CrngDevice::open_device(RandomDevice::Urandom).unwrap();
+        assert!(device.get_ent_cnt().is_ok(), "failed to get entropy count");
+
+        let device = CrngDevice::open_path(Path::new("/dev/urandom")).unwrap();
+        assert!(device.get_ent_cnt().is_ok(), "failed to get entropy count");
+
+        assert!(matches!(
+            CrngDevice::open_path(Path::new("/nonexistent/random")),
+            Err(CrngError::DeviceOpen(_))
+        ));
+    }
+
+    #[test]
+    fn test_add_randomness_log() {
+        use std::sync::Mutex;
+
+        struct CapturingLogger(Mutex<Vec<String>>);
+
+        impl log::Log for CapturingLogger {
+            fn enabled(&self, _: &log::Metadata) -> bool {
+                true
+            }
+
+            fn log(&self, record: &log::Record) {
+                self.0.lock().unwrap().push(record.args().to_string());
+            }
+
+            fn flush(&self) {}
+        }
+
+        static LOGGER: CapturingLogger = CapturingLogger(Mutex::new(Vec::new()));
+        log::set_logger(&LOGGER).unwrap();
+        log::set_max_level(log::LevelFilter::Debug);
+
+        // logged before the ioctl, so the result does not matter without root
+        let device = CrngDevice::open().unwrap();
+        let _ = device.add_randomness(&[0u8; 17], 0);
+
+        let messages = LOGGER.0.lock().unwrap();
+        assert!(messages.iter().any(|m| m.starts_with("Write 17 Byte ")));
+    }
+
+    #[test]
+    fn test_chunk_bits() {
+        let total = 5000;
+        let ent_bits = 39_999;
+        let mut claimed = 0;
+        let mut start = 0;
+        for end in [2048, 4096, 5000] {
+            let bits = chunk_bits(total, start, end, ent_bits);
+            assert!(usize::try_from(bits).unwrap() <= (end - start) * 8);
+            claimed += bits;
+            start = end;
+        }
+        assert_eq!(claimed, ent_bits);
+    }
+
+    #[test]
+    fn test_check_overclaim() {
+        assert!(check_overclaim(&[0u8; 4], 32).is_ok());
+        assert!(check_overclaim(&[], 0).is_ok());
+        assert!(matches!(
+            check_overclaim(&[0u8; 4], 33),
+            Err(CrngError::EntropyOverclaim {
+                ent_bits: 33,
+                max_bits: 32
+            })
+        ));
+
+        let largest = usize::MAX / 8;
+        assert_eq!(buffer_bits(largest), u64::try_from(largest).unwrap() * 8);
+        #[cfg(target_pointer_width = "64")]
+        assert_eq!(buffer_bits(usize::MAX), u64::MAX);
+    }
+
+    #[test]
+    fn test_add_randomness_rejected() {
+        // validated before the ioctl, so no root privileges are needed
+        let device = CrngDevice::open().unwrap();
+        assert!(matches!(
+            device.add_randomness(&[0u8; 16], 16 * 8 + 1),
+            Err(CrngError::EntropyOverclaim {
+                ent_bits: 129,
+                max_bits: 128
+            })
+        ));
+        assert!(matches!(
+            device.add_randomness_chunked(&[0u8; 16], u32::MAX),
+            Err(CrngError::EntropyOverclaim { .. })
+        ));
+        assert!(matches!(
+            device.add_randomness(&[0u8; 16], 1 << 31),
+            Err(CrngError::EntropyOverclaim {
+                ent_bits: 0x8000_0000,
+                max_bits: 0x7fff_ffff
+            })
+        ));
+        assert!(matches!(
+            device.add_randomness(&[0u8; ioctl_defs::MAX_BUFFER_SIZE + 1], 0),
+            Err(CrngError::BufferTooLarge)
+        ));
+    }
+
+    #[test]
+    fn test_add_randomness_chunked() {
+        if !Uid::effective().is_root() {
+            println!("Skipping test: requires root privileges");
+            return;
+        }
+
+        let device = CrngDevice::open().unwrap();
+        assert!(
+            device
+                .add_randomness_chunked(&[0x55; 8192], 8192 * 8)
+                .is_ok()
+        );
+        assert!(
+            device
+                .add_randomness_chunked(&[0x55; 8192], 8192 * 8 + 1)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_crng_device_privileged() {
+        if !Uid::effective().is_root() {
+            println!("Skipping test: requires root privileges");
+            return;
+        }
+
+        let device = CrngDevice::open().unwrap();
+        assert!(device.add_randomness(&[0u8; 32], 256).is_ok());
+        assert!(device.add_to_ent_cnt(32).is_ok());
+        assert!(device.reseed().is_ok());
+        assert!(device.get_ent_cnt().is_ok());
+    }
+}
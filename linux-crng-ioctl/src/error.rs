@@ -0,0 +1,149 @@
+use nix::errno::Errno;
+
+/// Errors of kernel CRNG operations.
+///
+/// Converts into `anyhow::Error` via `?` like any other `std::error::Error`.
+#[derive(Debug)]
+pub enum CrngError {
+    /// The random device could not be opened
+    DeviceOpen(std::io::Error),
+    /// An ioctl on the random device failed
+    Ioctl(Errno),
+    /// The ioctl requires `CAP_SYS_ADMIN` (`EPERM`)
+    PermissionDenied,
+    /// More entropy bits were claimed than the buffer holds or the kernel accepts
+    EntropyOverclaim {
+        /// Claimed entropy bits
+        ent_bits: u32,
+        /// Maximum number of bits which can be claimed
+        max_bits: u64,
+    },
+    /// The buffer exceeds the size a single ioctl accepts
+    BufferTooLarge,
+    /// The kernel CRNG is not seeded yet (`EAGAIN` on a non-blocking read), retry later
+    NotReady,
+    /// Interrupted by a signal before any data was read (`EINTR`), retry immediately
+    Interrupted,
+    /// The buffer is outside the accessible address space (`EFAULT`)
+    BadAddress,
+    /// Invalid flags were given (`EINVAL`)
+    InvalidFlags,
+    /// The operation is not supported by the running kernel (`ENOSYS`)
+    Unsupported,
+    /// Any other OS error
+    Os(Errno),
+    /// The kernel entropy count could not be read
+    Proc(crate::proc::ProcError),
+    /// The condition was not met before the timeout elapsed
+    Timeout,
+}
+
+impl std::fmt::Display for CrngError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::DeviceOpen(e) => write!(f, "cannot open random device: {e}"),
+            Self::Ioctl(e) => write!(f, "ioctl failed: {e}"),
+            Self::PermissionDenied => write!(f, "permission denied, CAP_SYS_ADMIN required"),
+            Self::EntropyOverclaim { ent_bits, max_bits } => write!(
+                f,
+                "claimed {ent_bits} bits of entropy, but at most {max_bits} are possible"
+            ),
+            Self::BufferTooLarge => write!(
+                f,
+                "buffer exceeds {} Byte accepted by the kernel CRNG input pool",
+                crate::ioctl_defs::MAX_BUFFER_SIZE
+            ),
+            Self::NotReady => write!(f, "kernel CRNG is not ready"),
+            Self::Interrupted => write!(f, "interrupted by signal"),
+            Self::BadAddress => write!(f, "buffer outside of accessible address space"),
+            Self::InvalidFlags => write!(f, "invalid flags"),
+            Self::Unsupported => write!(f, "not supported by the kernel"),
+            Self::Os(e) => write!(f, "OS error: {e}"),
+            Self::Proc(e) => write!(f, "cannot read entropy count: {e}"),
+            Self::Timeout => write!(f, "timed out"),
+        }
+    }
+}
+
+impl std::error::Error for CrngError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::DeviceOpen(e) => Some(e),
+            Self::Proc(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl CrngError {
+    /// Maps the errno of a failed ioctl call.
+    #[must_use]
+    pub fn from_ioctl(errno: Errno) -> Self {
+        match errno {
+            Errno::EPERM => Self::PermissionDenied,
+            e => Self::Ioctl(e),
+        }
+    }
+}
+
+impl From<crate::proc::ProcError> for CrngError {
+    fn from(e: crate::proc::ProcError) -> Self {
+        Self::Proc(e)
+    }
+}
+
+impl From<Errno> for CrngError {
+    fn from(errno: Errno) -> Self {
+        match errno {
+            Errno::EAGAIN => Self::NotReady,
+            Errno::EINTR => Self::Interrupted,
+            Errno::EFAULT => Self::BadAddress,
+            Errno::EINVAL => Self::InvalidFlags,
+            Errno::ENOSYS => Self::Unsupported,
+            e => Self::Os(e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_errno_mapping() {
+        assert!(matches!(
+            CrngError::from(Errno::EAGAIN),
+            CrngError::NotReady
+        ));
+        assert!(matches!(
+            CrngError::from(Errno::EINTR),
+            CrngError::Interrupted
+        ));
+        assert!(matches!(
+            CrngError::from(Errno::EFAULT),
+            CrngError::BadAddress
+        ));
+        assert!(matches!(
+            CrngError::from(Errno::EPERM),
+            CrngError::Os(Errno::EPERM)
+        ));
+    }
+
+    #[test]
+    fn test_ioctl_errno_mapping() {
+        assert!(matches!(
+            CrngError::from_ioctl(Errno::EPERM),
+            CrngError::PermissionDenied
+        ));
+        assert!(matches!(
+            CrngError::from_ioctl(Errno::ENOTTY),
+            CrngError::Ioctl(Errno::ENOTTY)
+        ));
+
+        let err = anyhow::Error::from(CrngError::EntropyOverclaim {
+            ent_bits: 9,
+            max_bits: 8,
+        });
+        assert!(err.downcast_ref::<CrngError>().is_some());
+    }
+}
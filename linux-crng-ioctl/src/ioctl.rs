@@ -1,7 +1,5 @@
-use crate::ioctl_defs;
-use anyhow::{Error, Result, anyhow};
-use log::{debug, error};
-use std::{fs::File, os::fd::AsRawFd};
+use crate::device::CrngDevice;
+use crate::error::CrngError;
 
 /// Gets the current entropy count from the kernel's random number generator.
 ///
@@ -28,18 +26,8 @@ use std::{fs::File, os::fd::AsRawFd};
 /// # Ok(())
 /// # }
 /// ```
-pub fn get_ent_cnt() -> Result<i32> {
-    let random_file = File::create("/dev/random")?;
-    let fd = random_file.as_raw_fd();
-    let mut ent_cnt = 0;
-
-    let ret = unsafe { ioctl_defs::rnd_get_ent_cnt(fd, &mut ent_cnt) };
-    if let Ok(0) = ret {
-        Ok(ent_cnt)
-    } else {
-        error!("ioctl returned with error");
-        Err(anyhow!("Failed to fetch entropy level from kernel"))
-    }
+pub fn get_ent_cnt() -> Result<i32, CrngError> {
+    CrngDevice::open()?.get_ent_cnt()
 }
 
 /// Adds to (or subtracts from) the kernel's entropy count estimation.
@@ -72,17 +60,8 @@ pub fn get_ent_cnt() -> Result<i32> {
 ///
 /// # Security
 /// Requires root privileges to execute successfully.
-pub fn add_to_ent_cnt(ent_cnt: i32) -> Result<()> {
-    let random_file = File::create("/dev/random")?;
-    let fd = random_file.as_raw_fd();
-
-    let ret = unsafe { ioctl_defs::rnd_add_to_ent_cnt(fd, &ent_cnt) };
-    if let Ok(0) = ret {
-        Ok(())
-    } else {
-        error!("ioctl returned with error");
-        Err(anyhow!("Failed to add to ent cnt"))
-    }
+pub fn add_to_ent_cnt(ent_cnt: i32) -> Result<(), CrngError> {
+    CrngDevice::open()?.add_to_ent_cnt(ent_cnt)
 }
 
 /// Adds random data to the kernel's entropy pool.
@@ -101,10 +80,9 @@ pub fn add_to_ent_cnt(ent_cnt: i32) -> Result<()> {
 /// # Errors
 /// - Returns error if not running with root privileges
 /// - Returns error if unable to open `/dev/random`
-/// - Returns error if `ent_bits` claims more entropy than possible (`buffer_length` * 8)
+/// - Returns `EntropyOverclaim` if `ent_bits` exceeds `buffer_length` * 8 or `i32::MAX`
 /// - Returns error if buffer size exceeds `MAX_BUFFER_SIZE` (2048 bytes)
 /// - Returns error if the ioctl call to add entropy fails
-/// - Returns error if integer conversion fails for buffer size or entropy bits
 ///
 /// # Example
 /// ```no_run
@@ -120,49 +98,81 @@ pub fn add_to_ent_cnt(ent_cnt: i32) -> Result<()> {
 /// # Security
 /// - Requires root privileges
 /// - Be careful not to overestimate entropy to maintain system security
-pub fn add_randomness_to_kernel(entropy: &[u8], ent_bits: u32) -> Result<()> {
-    let random_file = File::create("/dev/random")?;
-    let fd = random_file.as_raw_fd();
-
-    if usize::try_from(ent_bits)? > entropy.len() * 8 {
-        return Err(anyhow!("Do not claim more entropy than buffer length * 8!"));
-    }
-
-    if entropy.len() > ioctl_defs::MAX_BUFFER_SIZE {
-        return Err(anyhow!(
-            "This implementation currently can write up to {} Byte to kernel CRNG input pool",
-            ioctl_defs::MAX_BUFFER_SIZE
-        ));
-    }
+pub fn add_randomness_to_kernel(entropy: &[u8], ent_bits: u32) -> Result<(), CrngError> {
+    CrngDevice::open()?.add_randomness(entropy, ent_bits)
+}
 
-    debug!(
-        "Write {} Byte to /dev/random, accounted with {} Bit entropy",
-        64, ent_bits
-    );
+/// Mixes data into the kernel's input pool without crediting entropy.
+///
+/// Unlike `add_randomness_to_kernel`, this writes to `/dev/random` instead of using the
+/// `RNDADDENTROPY` ioctl. The data is mixed into the pool, but the entropy count is left
+/// unchanged, so the kernel does not consider itself better seeded afterwards. In
+/// return, no privileges are needed.
+///
+/// # Returns
+/// - `Ok(usize)` - Number of bytes written
+/// - `Err` - If the device cannot be opened or written
+///
+/// # Errors
+/// - Returns error if unable to open `/dev/random` for writing
+/// - Returns error if the write fails
+pub fn write_without_crediting(data: &[u8]) -> Result<usize, CrngError> {
+    CrngDevice::open()?.write_without_crediting(data)
+}
 
-    let mut pool_info = ioctl_defs::KernelRandPoolInfo {
-        header: ioctl_defs::KernelRandPoolInfoHeader {
-            entropy_bits: i32::try_from(ent_bits)?,
-            buf_size_byte: i32::try_from(entropy.len())?,
-        },
-        buf: [0; ioctl_defs::MAX_BUFFER_SIZE],
-    };
-    pool_info.buf[0..entropy.len()].copy_from_slice(entropy);
+/// Adds random data of any size to the kernel's entropy pool.
+///
+/// Works like `add_randomness_to_kernel`, but splits `entropy` into chunks of at most
+/// `MAX_BUFFER_SIZE` bytes, which are added with one ioctl each. The claimed `ent_bits`
+/// are distributed proportionally to the chunk sizes, in total exactly `ent_bits` are
+/// claimed.
+///
+/// # Errors
+/// - Returns `EntropyOverclaim` if `ent_bits` exceeds `buffer_length` * 8
+/// - Returns all errors of `add_randomness_to_kernel` for any chunk
+///
+/// # Security
+/// - Requires root privileges
+pub fn add_randomness_to_kernel_chunked(entropy: &[u8], ent_bits: u32) -> Result<(), CrngError> {
+    CrngDevice::open()?.add_randomness_chunked(entropy, ent_bits)
+}
 
-    #[allow(clippy::ptr_as_ptr)]
-    let res = unsafe {
-        ioctl_defs::rnd_add_entropy(
-            fd,
-            std::ptr::addr_of!(pool_info) as *const ioctl_defs::KernelRandPoolInfoHeader,
-        )
-    };
+/// Outcome of crediting entropy to the kernel, see `add_randomness_to_kernel_counted`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CreditReport {
+    /// Number of bytes written to the input pool
+    pub bytes_written: usize,
+    /// Number of entropy bits claimed for the written bytes
+    pub requested_bits: u32,
+    /// Change of the kernel entropy count measured around the write
+    pub credited_bits: i32,
+}
 
-    if let Ok(0) = res {
-        Ok(())
-    } else {
-        error!("ioctl returned with error");
-        Err(anyhow!("Failed to add entropy to kernel"))
-    }
+/// Adds random data to the kernel's entropy pool and reports the effective credit.
+///
+/// Works like `add_randomness_to_kernel`, but brackets the write with reads of the
+/// kernel entropy count. The kernel caps the entropy count at the pool size, so the
+/// measured change can be smaller than the claimed bits. Concurrent consumers of the
+/// pool can make it smaller as well.
+///
+/// # Arguments
+/// * `entropy` - Byte slice containing the random data to add
+/// * `ent_bits` - Number of bits of entropy claimed to be in the data
+///
+/// # Returns
+/// - `Ok(CreditReport)` - Written bytes, claimed and measured entropy
+/// - `Err` - If there's an error accessing the kernel or insufficient permissions
+///
+/// # Errors
+/// - Returns all errors of `get_ent_cnt` and `add_randomness_to_kernel`
+///
+/// # Security
+/// - Requires root privileges
+pub fn add_randomness_to_kernel_counted(
+    entropy: &[u8],
+    ent_bits: u32,
+) -> Result<CreditReport, CrngError> {
+    CrngDevice::open()?.add_randomness_counted(entropy, ent_bits)
 }
 
 /// Clears the kernel's entropy count to zero.
@@ -183,17 +193,8 @@ pub fn add_randomness_to_kernel(entropy: &[u8], ent_bits: u32) -> Result<()> {
 /// # Security
 /// - Requires root privileges
 /// - Use with caution as this affects system-wide entropy estimation
-pub fn clear_entropy_count() -> Result<(), Error> {
-    let random_file = File::create("/dev/random")?;
-    let fd = random_file.as_raw_fd();
-
-    match unsafe { ioctl_defs::rnd_zap_ent_cnt(fd) } {
-        Ok(0) => {
-            debug!("Cleared kernel CRNG entropy count to 0");
-            Ok(())
-        }
-        _ => Err(anyhow!("Cannot clear CRNG entropy count to 0")),
-    }
+pub fn clear_entropy_count() -> Result<(), CrngError> {
+    CrngDevice::open()?.clear_entropy_count()
 }
 
 /// Clears the kernel's entropy pool and associated counters.
@@ -214,17 +215,8 @@ pub fn clear_entropy_count() -> Result<(), Error> {
 /// # Security
 /// - Requires root privileges
 /// - Use with extreme caution as this affects system-wide randomness generation
-pub fn clear_pool() -> Result<(), Error> {
-    let random_file = File::create("/dev/random")?;
-    let fd = random_file.as_raw_fd();
-
-    match unsafe { ioctl_defs::rnd_clear_pool(fd) } {
-        Ok(0) => {
-            debug!("Forcefully cleared kernel CRNG pool");
-            Ok(())
-        }
-        _ => Err(anyhow!("Cannot clear CRNG pool")),
-    }
+pub fn clear_pool() -> Result<(), CrngError> {
+    CrngDevice::open()?.clear_pool()
 }
 
 /// Forces the kernel's CRNG (Cryptographic Random Number Generator) to reseed.
@@ -243,24 +235,17 @@ pub fn clear_pool() -> Result<(), Error> {
 ///
 /// # Security
 /// - Requires root privileges
-pub fn force_kernel_crng_reseed() -> Result<(), Error> {
-    let random_file = File::create("/dev/random")?;
-    let fd = random_file.as_raw_fd();
-
-    match unsafe { ioctl_defs::rnd_reseed_crng(fd) } {
-        Ok(0) => {
-            debug!("Forcefully reseeded kernel CRNG");
-            Ok(())
-        }
-        _ => Err(anyhow!("Cannot reseed CRNG")),
-    }
+pub fn force_kernel_crng_reseed() -> Result<(), CrngError> {
+    CrngDevice::open()?.reseed()
 }
 
 #[cfg(test)]
 mod tests {
+    use crate::error::CrngError;
     use crate::ioctl::{
-        add_randomness_to_kernel, add_to_ent_cnt, clear_entropy_count, clear_pool,
-        force_kernel_crng_reseed, get_ent_cnt,
+        add_randomness_to_kernel, add_randomness_to_kernel_chunked,
+        add_randomness_to_kernel_counted, add_to_ent_cnt, clear_entropy_count, clear_pool,
+        force_kernel_crng_reseed, get_ent_cnt, write_without_crediting,
     };
     use crate::ioctl_defs;
     use nix::unistd::Uid;
@@ -314,7 +299,7 @@ mod tests {
         let oversized_buffer = vec![0x55; ioctl_defs::MAX_BUFFER_SIZE + 1];
         let result = add_randomness_to_kernel(&oversized_buffer, 8);
         assert!(
-            result.is_err(),
+            matches!(result, Err(CrngError::BufferTooLarge)),
             "{}",
             format!(
                 "Expected error for buffer size larger than {}",
@@ -336,6 +321,37 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_write_without_crediting() {
+        assert_eq!(write_without_crediting(&[0x55; 64]).unwrap(), 64);
+    }
+
+    #[test]
+    fn test_add_entropy_chunked() {
+        if !Uid::effective().is_root() {
+            println!("Skipping test: requires root privileges");
+            return;
+        }
+
+        assert!(
+            add_randomness_to_kernel_chunked(&[0x55; 8 * 1024], 8 * 1024 * 8).is_ok(),
+            "failed to add chunked randomness to kernel"
+        );
+    }
+
+    #[test]
+    fn test_add_entropy_counted() {
+        if !Uid::effective().is_root() {
+            println!("Skipping test: requires root privileges");
+            return;
+        }
+
+        let report = add_randomness_to_kernel_counted(&[0u8; 32], 256).unwrap();
+        assert_eq!(report.bytes_written, 32);
+        assert_eq!(report.requested_bits, 256);
+        assert!(report.credited_bits <= 256);
+    }
+
     #[test]
     fn test_clear_entropy_count() {
         if !Uid::effective().is_root() {
@@ -1,7 +1,34 @@
+//! Wrappers for the Linux kernel RNG interfaces.
+//!
+//! All ioctls are implemented once by `device::CrngDevice`. The free functions of the
+//! `ioctl` module open a throwaway device and forward to it:
+//!
+//! ```no_run
+//! use linux_crng_ioctl::device::CrngDevice;
+//! use linux_crng_ioctl::ioctl::get_ent_cnt;
+//! # fn main() -> anyhow::Result<()> {
+//! let device = CrngDevice::open()?;
+//! println!("{} / {} bits", device.get_ent_cnt()?, get_ent_cnt()?);
+//! # Ok(())
+//! # }
+//! ```
+
 mod ioctl_defs;
 
+/// typed errors
+pub mod error;
+
 /// `/proc/sys/kernel/random/*` related functions
 pub mod proc;
 
 /// ioctl related functions
 pub mod ioctl;
+
+/// `getrandom(2)` wrapper
+pub mod syscall;
+
+/// privilege probing
+pub mod privilege;
+
+/// kernel entropy device abstraction
+pub mod device;
@@ -0,0 +1,51 @@
+use crate::ioctl::add_to_ent_cnt;
+use nix::unistd::Uid;
+
+/// Privileges of the current process with respect to the kernel CRNG.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum PrivilegeLevel {
+    /// Only reading randomness and writing uncredited data is possible
+    Unprivileged,
+    /// Entropy can be credited (`CAP_SYS_ADMIN` without being root)
+    CanCredit,
+    /// Running as root, all operations are available
+    FullAdmin,
+}
+
+/// Probes which kernel CRNG operations the current process may perform.
+///
+/// The probe adds 0 bits to the kernel entropy count, which requires `CAP_SYS_ADMIN`
+/// but leaves the kernel state untouched.
+///
+/// # Example
+/// ```no_run
+/// # use linux_crng_ioctl::privilege::{privilege_level, PrivilegeLevel};
+/// if privilege_level() == PrivilegeLevel::Unprivileged {
+///     println!("Crediting entropy is not possible");
+/// }
+/// ```
+#[must_use]
+pub fn privilege_level() -> PrivilegeLevel {
+    if add_to_ent_cnt(0).is_err() {
+        PrivilegeLevel::Unprivileged
+    } else if Uid::effective().is_root() {
+        PrivilegeLevel::FullAdmin
+    } else {
+        PrivilegeLevel::CanCredit
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_privilege_level() {
+        let level = privilege_level();
+        if Uid::effective().is_root() {
+            assert_eq!(level, PrivilegeLevel::FullAdmin);
+        } else {
+            assert_ne!(level, PrivilegeLevel::FullAdmin);
+        }
+    }
+}
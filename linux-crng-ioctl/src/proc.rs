@@ -1,6 +1,106 @@
-use anyhow::Error;
-use std::fs::File;
-use std::io::Read;
+use crate::error::CrngError;
+use crate::syscall::{GetrandomFlags, read_getrandom};
+use std::fs::{File, OpenOptions};
+use std::io::{Read, Write};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+/// Errors of reading `/proc/sys/kernel/random/*` files.
+#[derive(Debug)]
+pub enum ProcError {
+    /// The file does not exist, e.g. because the kernel does not expose this knob
+    NotFound,
+    /// The file could not be written without root privileges
+    PermissionDenied,
+    /// The file could not be written because `/proc/sys` is mounted read-only, e.g. in a container
+    ReadOnly,
+    /// The file could not be opened or read
+    Io(std::io::Error),
+    /// The file content could not be parsed
+    Parse(std::num::ParseIntError),
+    /// The file content is not valid UTF-8
+    InvalidUtf8,
+    /// The file content is not a valid UUID
+    Uuid(uuid::Error),
+}
+
+impl std::fmt::Display for ProcError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NotFound => write!(f, "proc file not found"),
+            Self::PermissionDenied => write!(f, "permission denied, root required"),
+            Self::ReadOnly => write!(f, "proc file is on a read-only filesystem"),
+            Self::Io(e) => write!(f, "failed to read proc file: {e}"),
+            Self::Parse(e) => write!(f, "failed to parse proc file: {e}"),
+            Self::InvalidUtf8 => write!(f, "proc file content is not valid UTF-8"),
+            Self::Uuid(e) => write!(f, "failed to parse UUID from proc file: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for ProcError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(e) => Some(e),
+            Self::Parse(e) => Some(e),
+            Self::Uuid(e) => Some(e),
+            Self::NotFound | Self::PermissionDenied | Self::ReadOnly | Self::InvalidUtf8 => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for ProcError {
+    fn from(e: std::io::Error) -> Self {
+        match e.kind() {
+            std::io::ErrorKind::NotFound => Self::NotFound,
+            std::io::ErrorKind::PermissionDenied => Self::PermissionDenied,
+            std::io::ErrorKind::ReadOnlyFilesystem => Self::ReadOnly,
+            _ => Self::Io(e),
+        }
+    }
+}
+
+impl From<uuid::Error> for ProcError {
+    fn from(e: uuid::Error) -> Self {
+        Self::Uuid(e)
+    }
+}
+
+impl From<std::num::ParseIntError> for ProcError {
+    fn from(e: std::num::ParseIntError) -> Self {
+        Self::Parse(e)
+    }
+}
+
+fn read_string<R: Read>(mut reader: R) -> Result<String, ProcError> {
+    let mut buf = Vec::new();
+    reader.read_to_end(&mut buf)?;
+    String::from_utf8(buf).map_err(|_| ProcError::InvalidUtf8)
+}
+
+fn parse_u32<R: Read>(reader: R) -> Result<u32, ProcError> {
+    Ok(read_string(reader)?.trim().parse::<u32>()?)
+}
+
+fn read_proc_string(path: &Path) -> Result<String, ProcError> {
+    read_string(File::open(path)?)
+}
+
+fn read_proc_uuid(path: &Path) -> Result<uuid::Uuid, ProcError> {
+    Ok(uuid::Uuid::parse_str(read_proc_string(path)?.trim())?)
+}
+
+fn read_proc_u32(path: &Path) -> Result<u32, ProcError> {
+    parse_u32(File::open(path)?)
+}
+
+fn write_proc_u32(path: &Path, value: u32) -> Result<(), ProcError> {
+    OpenOptions::new()
+        .write(true)
+        .open(path)?
+        .write_all(value.to_string().as_bytes())?;
+    Ok(())
+}
 
 /// Reads the system's boot ID from `/proc/sys/kernel/random/boot_id`.
 ///
@@ -11,9 +111,9 @@ use std::io::Read;
 /// - `Err` - If there's an error reading the boot ID
 ///
 /// # Errors
-/// - Returns error if unable to open `/proc/sys/kernel/random/boot_id`
-/// - Returns error if unable to read from the file
-/// - Returns error if the file content is not valid UTF-8
+/// - Returns `NotFound` if `/proc/sys/kernel/random/boot_id` does not exist
+/// - Returns `Io` if unable to open or read the file
+/// - Returns `InvalidUtf8` if the file content is not valid UTF-8
 ///
 /// # Example
 /// ```no_run
@@ -25,11 +125,17 @@ use std::io::Read;
 /// # Ok(())
 /// # }
 /// ```
-pub fn boot_id() -> anyhow::Result<String, Error> {
-    let mut proc_file = File::open("/proc/sys/kernel/random/boot_id")?;
-    let mut boot_id = String::new();
-    proc_file.read_to_string(&mut boot_id)?;
-    Ok(boot_id)
+pub fn boot_id() -> Result<String, ProcError> {
+    read_proc_string(Path::new("/proc/sys/kernel/random/boot_id"))
+}
+
+/// Reads the system's boot ID from `/proc/sys/kernel/random/boot_id` as `Uuid`.
+///
+/// # Errors
+/// - Returns all errors of `boot_id`
+/// - Returns `Uuid` if the file content is not a valid UUID
+pub fn boot_id_uuid() -> Result<uuid::Uuid, ProcError> {
+    read_proc_uuid(Path::new("/proc/sys/kernel/random/boot_id"))
 }
 
 /// Reads the current available entropy from `/proc/sys/kernel/random/entropy_avail`.
@@ -41,10 +147,10 @@ pub fn boot_id() -> anyhow::Result<String, Error> {
 /// - `Err` - If there's an error reading the entropy value
 ///
 /// # Errors
-/// - Returns error if unable to open `/proc/sys/kernel/random/entropy_avail`
-/// - Returns error if unable to read from the file
-/// - Returns error if the file content is not valid UTF-8
-/// - Returns error if the content cannot be parsed as a u32
+/// - Returns `NotFound` if `/proc/sys/kernel/random/entropy_avail` does not exist
+/// - Returns `Io` if unable to open or read the file
+/// - Returns `InvalidUtf8` if the file content is not valid UTF-8
+/// - Returns `Parse` if the content cannot be parsed as a u32
 ///
 /// # Example
 /// ```no_run
@@ -56,11 +162,83 @@ pub fn boot_id() -> anyhow::Result<String, Error> {
 /// # Ok(())
 /// # }
 /// ```
-pub fn entropy_avail() -> anyhow::Result<u32, Error> {
-    let mut proc_file = File::open("/proc/sys/kernel/random/entropy_avail")?;
-    let mut entropy_avail = String::new();
-    proc_file.read_to_string(&mut entropy_avail)?;
-    Ok(entropy_avail.trim().parse::<u32>()?)
+pub fn entropy_avail() -> Result<u32, ProcError> {
+    read_proc_u32(Path::new("/proc/sys/kernel/random/entropy_avail"))
+}
+
+/// Reads the kernel entropy count in bits from the best available source.
+///
+/// Prefers the `RNDGETENTCNT` ioctl of `ioctl::get_ent_cnt` and falls back to
+/// `entropy_avail` if `/dev/random` cannot be opened, e.g. in a sandbox. Both report
+/// the same counter, the signed ioctl value is clamped to zero.
+///
+/// # Returns
+/// - `Ok(u32)` - The available entropy in bits
+/// - `Err` - If neither source can be read
+///
+/// # Errors
+/// - Returns all errors of `entropy_avail` if the ioctl fails as well
+pub fn entropy_bits_available() -> Result<u32, ProcError> {
+    match crate::ioctl::get_ent_cnt() {
+        Ok(bits) => Ok(u32::try_from(bits).unwrap_or(0)),
+        Err(e) => {
+            log::debug!("Reading entropy count via ioctl failed: {e}, falling back to proc");
+            entropy_avail()
+        }
+    }
+}
+
+/// First pause between two polls of `wait_for_entropy`, doubled after every poll.
+const WAIT_INITIAL_BACKOFF: Duration = Duration::from_millis(1);
+
+/// Longest pause between two polls of `wait_for_entropy`.
+const WAIT_MAX_BACKOFF: Duration = Duration::from_millis(100);
+
+/// Blocks until the kernel entropy count reaches `min_bits`.
+///
+/// Polls `entropy_avail` with an exponential backoff from 1 ms up to 100 ms between
+/// polls, the count is checked at least once. A `timeout` too large to represent as a
+/// deadline waits forever.
+///
+/// # Returns
+/// - `Ok(())` - At least `min_bits` are available
+/// - `Err` - If the count cannot be read or `timeout` elapsed
+///
+/// # Errors
+/// - Returns `Timeout` if less than `min_bits` are available after `timeout`
+/// - Returns `Proc` with any error of `entropy_avail`
+///
+/// # Example
+/// ```no_run
+/// # use linux_crng_ioctl::proc::wait_for_entropy;
+/// # use std::time::Duration;
+/// # fn main() -> anyhow::Result<()> {
+/// wait_for_entropy(256, Duration::from_secs(30))?;
+/// # Ok(())
+/// # }
+/// ```
+pub fn wait_for_entropy(min_bits: u32, timeout: Duration) -> Result<(), CrngError> {
+    let deadline = Instant::now().checked_add(timeout);
+    let mut backoff = WAIT_INITIAL_BACKOFF;
+
+    loop {
+        if entropy_avail()? >= min_bits {
+            return Ok(());
+        }
+
+        let pause = match deadline {
+            Some(deadline) => {
+                let now = Instant::now();
+                if now >= deadline {
+                    return Err(CrngError::Timeout);
+                }
+                backoff.min(deadline - now)
+            }
+            None => backoff,
+        };
+        std::thread::sleep(pause);
+        backoff = (backoff * 2).min(WAIT_MAX_BACKOFF);
+    }
 }
 
 /// Reads the entropy pool size from `/proc/sys/kernel/random/poolsize`.
@@ -72,15 +250,12 @@ pub fn entropy_avail() -> anyhow::Result<u32, Error> {
 /// - `Err` - If there's an error reading the pool size
 ///
 /// # Errors
-/// - Returns error if unable to open `/proc/sys/kernel/random/poolsize`
-/// - Returns error if unable to read from the file
-/// - Returns error if the file content is not valid UTF-8
-/// - Returns error if the content cannot be parsed as a u32
-pub fn poolsize() -> anyhow::Result<u32, Error> {
-    let mut proc_file = File::open("/proc/sys/kernel/random/poolsize")?;
-    let mut poolsize = String::new();
-    proc_file.read_to_string(&mut poolsize)?;
-    Ok(poolsize.trim().parse::<u32>()?)
+/// - Returns `NotFound` if `/proc/sys/kernel/random/poolsize` does not exist
+/// - Returns `Io` if unable to open or read the file
+/// - Returns `InvalidUtf8` if the file content is not valid UTF-8
+/// - Returns `Parse` if the content cannot be parsed as a u32
+pub fn poolsize() -> Result<u32, ProcError> {
+    read_proc_u32(Path::new("/proc/sys/kernel/random/poolsize"))
 }
 
 /// Generates a new UUID using the kernel's random number generator.
@@ -92,9 +267,9 @@ pub fn poolsize() -> anyhow::Result<u32, Error> {
 /// - `Err` - If there's an error generating or reading the UUID
 ///
 /// # Errors
-/// - Returns error if unable to open `/proc/sys/kernel/random/uuid`
-/// - Returns error if unable to read from the file
-/// - Returns error if the file content is not valid UTF-8
+/// - Returns `NotFound` if `/proc/sys/kernel/random/uuid` does not exist
+/// - Returns `Io` if unable to open or read the file
+/// - Returns `InvalidUtf8` if the file content is not valid UTF-8
 ///
 /// # Example
 /// ```no_run
@@ -106,11 +281,19 @@ pub fn poolsize() -> anyhow::Result<u32, Error> {
 /// # Ok(())
 /// # }
 /// ```
-pub fn uuid() -> anyhow::Result<String, Error> {
-    let mut proc_file = File::open("/proc/sys/kernel/random/uuid")?;
-    let mut uuid = String::new();
-    proc_file.read_to_string(&mut uuid)?;
-    Ok(uuid.trim().to_string())
+pub fn uuid() -> Result<String, ProcError> {
+    Ok(read_proc_string(Path::new("/proc/sys/kernel/random/uuid"))?
+        .trim()
+        .to_string())
+}
+
+/// Generates a new random (version 4) UUID like `uuid`, but returns it as `Uuid`.
+///
+/// # Errors
+/// - Returns all errors of `uuid`
+/// - Returns `Uuid` if the file content is not a valid UUID
+pub fn uuid_typed() -> Result<uuid::Uuid, ProcError> {
+    read_proc_uuid(Path::new("/proc/sys/kernel/random/uuid"))
 }
 
 /// Reads the minimum reseed time for /dev/urandom.
@@ -123,15 +306,31 @@ pub fn uuid() -> anyhow::Result<String, Error> {
 /// - `Err` - If there's an error reading the value
 ///
 /// # Errors
-/// - Returns error if unable to open `/proc/sys/kernel/random/urandom_min_reseed_secs`
-/// - Returns error if unable to read from the file
-/// - Returns error if the file content is not valid UTF-8
-/// - Returns error if the content cannot be parsed as a u32
-pub fn urandom_min_reseed_secs() -> anyhow::Result<u32, Error> {
-    let mut proc_file = File::open("/proc/sys/kernel/random/urandom_min_reseed_secs")?;
-    let mut min_reseed_secs = String::new();
-    proc_file.read_to_string(&mut min_reseed_secs)?;
-    Ok(min_reseed_secs.trim().parse::<u32>()?)
+/// - Returns `NotFound` if `/proc/sys/kernel/random/urandom_min_reseed_secs` does not exist
+/// - Returns `Io` if unable to open or read the file
+/// - Returns `InvalidUtf8` if the file content is not valid UTF-8
+/// - Returns `Parse` if the content cannot be parsed as a u32
+pub fn urandom_min_reseed_secs() -> Result<u32, ProcError> {
+    read_proc_u32(Path::new("/proc/sys/kernel/random/urandom_min_reseed_secs"))
+}
+
+/// Sets the minimum reseed time for /dev/urandom.
+///
+/// Writes `secs` to `/proc/sys/kernel/random/urandom_min_reseed_secs`.
+///
+/// # Errors
+/// - Returns `NotFound` if `/proc/sys/kernel/random/urandom_min_reseed_secs` does not exist
+/// - Returns `PermissionDenied` if not running with root privileges
+/// - Returns `ReadOnly` if `/proc/sys` is mounted read-only
+/// - Returns `Io` if unable to open or write the file
+///
+/// # Security
+/// Requires root privileges to execute successfully.
+pub fn set_urandom_min_reseed_secs(secs: u32) -> Result<(), ProcError> {
+    write_proc_u32(
+        Path::new("/proc/sys/kernel/random/urandom_min_reseed_secs"),
+        secs,
+    )
 }
 
 /// Reads the `write_wakeup_threshold` from `/proc/sys/kernel/random/write_wakeup_threshold`.
@@ -143,15 +342,185 @@ pub fn urandom_min_reseed_secs() -> anyhow::Result<u32, Error> {
 /// - `Err` - If there's an error reading the threshold
 ///
 /// # Errors
-/// - Returns error if unable to open `/proc/sys/kernel/random/write_wakeup_threshold`
-/// - Returns error if unable to read from the file
-/// - Returns error if the file content is not valid UTF-8
-/// - Returns error if the content cannot be parsed as a u32
-pub fn write_wakeup_threshold() -> anyhow::Result<u32, Error> {
-    let mut proc_file = File::open("/proc/sys/kernel/random/write_wakeup_threshold")?;
-    let mut write_wakeup_threshold = String::new();
-    proc_file.read_to_string(&mut write_wakeup_threshold)?;
-    Ok(write_wakeup_threshold.trim().parse::<u32>()?)
+/// - Returns `NotFound` if `/proc/sys/kernel/random/write_wakeup_threshold` does not exist
+/// - Returns `Io` if unable to open or read the file
+/// - Returns `InvalidUtf8` if the file content is not valid UTF-8
+/// - Returns `Parse` if the content cannot be parsed as a u32
+pub fn write_wakeup_threshold() -> Result<u32, ProcError> {
+    read_proc_u32(Path::new("/proc/sys/kernel/random/write_wakeup_threshold"))
+}
+
+/// Sets the `write_wakeup_threshold` in `/proc/sys/kernel/random/write_wakeup_threshold`.
+///
+/// # Errors
+/// - Returns `NotFound` if `/proc/sys/kernel/random/write_wakeup_threshold` does not exist
+/// - Returns `PermissionDenied` if not running with root privileges
+/// - Returns `ReadOnly` if `/proc/sys` is mounted read-only
+/// - Returns `Io` if unable to open or write the file
+///
+/// # Security
+/// Requires root privileges to execute successfully.
+pub fn set_write_wakeup_threshold(threshold: u32) -> Result<(), ProcError> {
+    write_proc_u32(
+        Path::new("/proc/sys/kernel/random/write_wakeup_threshold"),
+        threshold,
+    )
+}
+
+/// Sets `urandom_min_reseed_secs` and `write_wakeup_threshold` together.
+///
+/// Only values given as `Some` are written. If writing the wakeup threshold fails after
+/// the reseed time was changed, the previous reseed time is restored.
+///
+/// # Arguments
+/// * `reseed_secs` - New value of `/proc/sys/kernel/random/urandom_min_reseed_secs`
+/// * `wakeup_threshold` - New value of `/proc/sys/kernel/random/write_wakeup_threshold`
+///
+/// # Returns
+/// - `Ok(())` - If all given values were written
+/// - `Err` - If there's an error writing a value
+///
+/// # Errors
+/// - Returns `NotFound` if one of the files does not exist
+/// - Returns `PermissionDenied` if not running with root privileges
+/// - Returns `ReadOnly` if `/proc/sys` is mounted read-only
+/// - Returns `Io` if unable to open or write the files
+/// - Returns all errors of `urandom_min_reseed_secs` if both values are given
+///
+/// # Security
+/// Requires root privileges to execute successfully.
+pub fn tune(reseed_secs: Option<u32>, wakeup_threshold: Option<u32>) -> Result<(), ProcError> {
+    let previous_reseed_secs = match (reseed_secs, wakeup_threshold) {
+        (Some(_), Some(_)) => Some(urandom_min_reseed_secs()?),
+        _ => None,
+    };
+
+    if let Some(reseed_secs) = reseed_secs {
+        set_urandom_min_reseed_secs(reseed_secs)?;
+    }
+
+    if let Some(wakeup_threshold) = wakeup_threshold
+        && let Err(e) = set_write_wakeup_threshold(wakeup_threshold)
+    {
+        if let Some(previous) = previous_reseed_secs {
+            let _ = set_urandom_min_reseed_secs(previous);
+        }
+        return Err(e);
+    }
+
+    Ok(())
+}
+
+/// Reads the `read_wakeup_threshold` from `/proc/sys/kernel/random/read_wakeup_threshold`.
+///
+/// This value determined the threshold at which readers of /dev/random were woken up.
+/// It was removed in Linux 5.6, newer kernels report `NotFound`.
+///
+/// # Returns
+/// - `Ok(u32)` - The current read wakeup threshold
+/// - `Err` - If there's an error reading the threshold
+///
+/// # Errors
+/// - Returns `NotFound` if `/proc/sys/kernel/random/read_wakeup_threshold` does not exist
+/// - Returns `Io` if unable to open or read the file
+/// - Returns `InvalidUtf8` if the file content is not valid UTF-8
+/// - Returns `Parse` if the content cannot be parsed as a u32
+pub fn read_wakeup_threshold() -> Result<u32, ProcError> {
+    read_proc_u32(Path::new("/proc/sys/kernel/random/read_wakeup_threshold"))
+}
+
+/// Values of the kernel random sysctls, see `read_all`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RandomSysctls {
+    /// Available entropy in bits, see `entropy_avail`
+    pub entropy_avail: u32,
+    /// Size of the entropy pool in bits, see `poolsize`
+    pub poolsize: u32,
+    /// Minimum reseed time of /dev/urandom in seconds, see `urandom_min_reseed_secs`
+    pub urandom_min_reseed_secs: u32,
+    /// Threshold at which writers to /dev/random are woken up, see `write_wakeup_threshold`
+    pub write_wakeup_threshold: u32,
+    /// Boot ID of the running system, see `boot_id`
+    pub boot_id: String,
+}
+
+/// Reads all kernel random sysctls in one call.
+///
+/// The values are read one after another, so `entropy_avail` may already have changed
+/// once the last value was read.
+///
+/// # Returns
+/// - `Ok(RandomSysctls)` - The current values
+/// - `Err` - If there's an error reading any value
+///
+/// # Errors
+/// - Returns all errors of `entropy_avail`, `poolsize`, `urandom_min_reseed_secs`,
+///   `write_wakeup_threshold` and `boot_id`
+///
+/// # Example
+/// ```no_run
+/// # use anyhow::Result;
+/// # use linux_crng_ioctl::proc::read_all;
+/// # fn main() -> Result<()> {
+/// let sysctls = read_all()?;
+/// println!("{} of {} bits", sysctls.entropy_avail, sysctls.poolsize);
+/// # Ok(())
+/// # }
+/// ```
+pub fn read_all() -> Result<RandomSysctls, ProcError> {
+    Ok(RandomSysctls {
+        entropy_avail: entropy_avail()?,
+        poolsize: poolsize()?,
+        urandom_min_reseed_secs: urandom_min_reseed_secs()?,
+        write_wakeup_threshold: write_wakeup_threshold()?,
+        boot_id: boot_id()?.trim().to_string(),
+    })
+}
+
+/// Checks whether the kernel CRNG is fully seeded.
+///
+/// Attempts a non-blocking `getrandom(2)` read of a single byte, which fails with
+/// `EAGAIN` as long as the CRNG is not initialized. Kernels before 3.17 lack
+/// `getrandom(2)`, there a read of `/dev/random` would not block once `entropy_avail`
+/// reaches `read_wakeup_threshold`, which is checked instead.
+///
+/// # Returns
+/// - `Ok(true)` - The CRNG is initialized
+/// - `Ok(false)` - The CRNG is not yet initialized
+/// - `Err` - If `getrandom(2)` fails for another reason
+///
+/// # Errors
+/// - Returns `Io` if `getrandom(2)` fails otherwise
+/// - Returns all errors of `entropy_avail` and `read_wakeup_threshold` on kernels
+///   without `getrandom(2)`
+///
+/// # Example
+/// ```no_run
+/// # use linux_crng_ioctl::proc::crng_ready;
+/// # fn main() -> anyhow::Result<()> {
+/// while !crng_ready()? {
+///     std::thread::sleep(std::time::Duration::from_millis(100));
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub fn crng_ready() -> Result<bool, ProcError> {
+    let mut buf = [0u8; 1];
+    let flags = GetrandomFlags {
+        nonblock: true,
+        ..GetrandomFlags::default()
+    };
+    loop {
+        match read_getrandom(&mut buf, flags) {
+            Ok(_) => return Ok(true),
+            Err(CrngError::Interrupted) => {}
+            Err(CrngError::NotReady) => return Ok(false),
+            Err(CrngError::Unsupported) => {
+                return Ok(entropy_avail()? >= read_wakeup_threshold()?);
+            }
+            Err(e) => return Err(ProcError::Io(std::io::Error::other(e))),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -168,11 +537,50 @@ mod tests {
         assert!(entropy_avail().is_ok());
     }
 
+    #[test]
+    fn test_wait_for_entropy() {
+        let start = Instant::now();
+        assert!(wait_for_entropy(0, Duration::from_secs(10)).is_ok());
+        assert!(wait_for_entropy(0, Duration::MAX).is_ok());
+        assert!(start.elapsed() < Duration::from_secs(1));
+
+        assert!(matches!(
+            wait_for_entropy(u32::MAX, Duration::from_millis(50)),
+            Err(CrngError::Timeout)
+        ));
+    }
+
+    #[test]
+    fn test_entropy_bits_available() {
+        let bits = entropy_bits_available().unwrap();
+        let ioctl = u32::try_from(crate::ioctl::get_ent_cnt().unwrap()).unwrap();
+        let proc = entropy_avail().unwrap();
+
+        // the count may change between the reads on kernels before 5.18
+        assert!(bits.abs_diff(ioctl) <= 128, "{bits} vs ioctl {ioctl}");
+        assert!(bits.abs_diff(proc) <= 128, "{bits} vs proc {proc}");
+        assert!(bits <= poolsize().unwrap());
+    }
+
     #[test]
     fn test_proc_poolsize() {
         assert!(poolsize().is_ok());
     }
 
+    #[test]
+    fn test_proc_boot_id_uuid() {
+        let id = boot_id_uuid().unwrap();
+        assert_eq!(id.to_string(), boot_id().unwrap().trim());
+        assert_eq!(boot_id_uuid().unwrap(), id);
+    }
+
+    #[test]
+    fn test_proc_uuid_typed() {
+        let first = uuid_typed().unwrap();
+        assert_eq!(first.get_version(), Some(uuid::Version::Random));
+        assert_ne!(uuid_typed().unwrap(), first);
+    }
+
     #[test]
     fn test_proc_urandom_min_reseed_secs() {
         assert!(urandom_min_reseed_secs().is_ok());
@@ -183,6 +591,102 @@ mod tests {
         assert!(uuid().is_ok());
     }
 
+    #[test]
+    fn test_tune() {
+        if !nix::unistd::Uid::effective().is_root() {
+            println!("Skipping test: requires root privileges");
+            return;
+        }
+
+        let reseed_secs = urandom_min_reseed_secs().unwrap();
+        let wakeup_threshold = write_wakeup_threshold().unwrap();
+
+        tune(Some(reseed_secs), Some(wakeup_threshold)).unwrap();
+        tune(None, None).unwrap();
+
+        assert_eq!(urandom_min_reseed_secs().unwrap(), reseed_secs);
+        assert_eq!(write_wakeup_threshold().unwrap(), wakeup_threshold);
+    }
+
+    #[test]
+    fn test_set_urandom_min_reseed_secs() {
+        if !nix::unistd::Uid::effective().is_root() {
+            assert!(set_urandom_min_reseed_secs(60).is_err());
+            return;
+        }
+
+        let original = urandom_min_reseed_secs().unwrap();
+        match set_urandom_min_reseed_secs(original + 1) {
+            Ok(()) => {
+                assert_eq!(urandom_min_reseed_secs().unwrap(), original + 1);
+                set_urandom_min_reseed_secs(original).unwrap();
+                assert_eq!(urandom_min_reseed_secs().unwrap(), original);
+            }
+            Err(ProcError::ReadOnly) => println!("Skipping test: /proc/sys is read-only"),
+            Err(e) => panic!("unexpected error: {e}"),
+        }
+    }
+
+    #[test]
+    fn test_set_write_wakeup_threshold() {
+        if !nix::unistd::Uid::effective().is_root() {
+            assert!(set_write_wakeup_threshold(64).is_err());
+            return;
+        }
+
+        let original = write_wakeup_threshold().unwrap();
+        match set_write_wakeup_threshold(original + 8) {
+            Ok(()) => {
+                assert_eq!(write_wakeup_threshold().unwrap(), original + 8);
+                set_write_wakeup_threshold(original).unwrap();
+                assert_eq!(write_wakeup_threshold().unwrap(), original);
+            }
+            Err(ProcError::ReadOnly) => println!("Skipping test: /proc/sys is read-only"),
+            Err(e) => panic!("unexpected error: {e}"),
+        }
+    }
+
+    #[test]
+    fn test_read_wakeup_threshold() {
+        match read_wakeup_threshold() {
+            Ok(_) | Err(ProcError::NotFound) => {}
+            Err(e) => panic!("unexpected error: {e}"),
+        }
+    }
+
+    #[test]
+    fn test_proc_not_found() {
+        assert!(matches!(
+            read_proc_u32(Path::new("/proc/sys/kernel/random/does_not_exist")),
+            Err(ProcError::NotFound)
+        ));
+    }
+
+    #[test]
+    fn test_proc_parse_error() {
+        assert!(matches!(
+            parse_u32(&b"garbage\n"[..]),
+            Err(ProcError::Parse(_))
+        ));
+        assert!(matches!(
+            read_string(&[0xFFu8][..]),
+            Err(ProcError::InvalidUtf8)
+        ));
+        assert_eq!(parse_u32(&b"256\n"[..]).unwrap(), 256);
+    }
+
+    #[test]
+    fn test_read_all() {
+        let sysctls = read_all().unwrap();
+        assert!(sysctls.entropy_avail <= sysctls.poolsize);
+        assert!(!sysctls.boot_id.is_empty());
+    }
+
+    #[test]
+    fn test_crng_ready() {
+        assert!(crng_ready().unwrap());
+    }
+
     #[test]
     fn test_write_wakeup_threshold() {
         assert!(write_wakeup_threshold().is_ok());
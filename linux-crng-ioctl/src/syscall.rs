@@ -0,0 +1,96 @@
+use crate::error::CrngError;
+use nix::errno::Errno;
+
+/// Flags of `getrandom(2)`, see `read_getrandom`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct GetrandomFlags {
+    /// `GRND_NONBLOCK`: fail with `CrngError::NotReady` instead of blocking until the
+    /// CRNG is initialized
+    pub nonblock: bool,
+    /// `GRND_RANDOM`: read with `/dev/random` semantics, which equal the default ones
+    /// since Linux 5.6
+    pub random: bool,
+}
+
+impl GetrandomFlags {
+    /// Raw flag bits passed to the kernel.
+    #[must_use]
+    pub fn bits(self) -> u32 {
+        let mut bits = 0;
+        if self.nonblock {
+            bits |= nix::libc::GRND_NONBLOCK;
+        }
+        if self.random {
+            bits |= nix::libc::GRND_RANDOM;
+        }
+        bits
+    }
+}
+
+/// Reads random bytes from the kernel CRNG via `getrandom(2)`.
+///
+/// Performs a single call, which can return fewer bytes than requested, e.g. for large
+/// buffers or when interrupted by a signal.
+///
+/// # Returns
+/// - `Ok(usize)` - Number of bytes read into `buf`
+/// - `Err` - If `getrandom(2)` fails
+///
+/// # Errors
+/// - Returns `NotReady` if `flags.nonblock` is set and the CRNG is not initialized yet
+/// - Returns `Interrupted` if a signal arrived before any data was read
+/// - Returns `Unsupported` if the kernel lacks `getrandom(2)`
+///
+/// # Example
+/// ```
+/// # use linux_crng_ioctl::syscall::{GetrandomFlags, read_getrandom};
+/// # fn main() -> anyhow::Result<()> {
+/// let mut buf = [0u8; 32];
+/// let read = read_getrandom(&mut buf, GetrandomFlags::default())?;
+/// assert_eq!(read, buf.len());
+/// # Ok(())
+/// # }
+/// ```
+pub fn read_getrandom(buf: &mut [u8], flags: GetrandomFlags) -> Result<usize, CrngError> {
+    getrandom(buf, flags.bits())
+}
+
+/// Calls `getrandom(2)` once with `flags`, returning the number of bytes read.
+pub(crate) fn getrandom(buf: &mut [u8], flags: u32) -> Result<usize, CrngError> {
+    let ret = unsafe { nix::libc::getrandom(buf.as_mut_ptr().cast(), buf.len(), flags) };
+    usize::try_from(ret).map_err(|_| Errno::last().into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_getrandom_errors() {
+        let mut buf = [0u8; 32];
+        assert_eq!(getrandom(&mut buf, 0).unwrap(), 32);
+        assert!(matches!(
+            getrandom(&mut buf, u32::MAX),
+            Err(CrngError::InvalidFlags)
+        ));
+    }
+
+    #[test]
+    fn test_read_getrandom() {
+        let mut buf = [0u8; 32];
+        assert_eq!(
+            read_getrandom(&mut buf, GetrandomFlags::default()).unwrap(),
+            32
+        );
+        assert!(buf.iter().any(|&b| b != 0));
+
+        let flags = GetrandomFlags {
+            nonblock: true,
+            random: true,
+        };
+        assert_eq!(
+            flags.bits(),
+            nix::libc::GRND_NONBLOCK | nix::libc::GRND_RANDOM
+        );
+    }
+}
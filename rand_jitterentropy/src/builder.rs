@@ -0,0 +1,247 @@
+use crate::{JitterConfig, JitterEntropyError, RandJitterEntropy, TimerMode};
+use libjitterentropy_sys::jitterentropy::{
+    JENT_DISABLE_MEMORY_ACCESS, JENT_FLAGS_TO_MEMSIZE_SHIFT, JENT_FORCE_FIPS,
+    JENT_MAX_MEMSIZE_OFFSET, JENT_NTG1,
+};
+
+/// Block size used if only `memory_blocks` is given, one cache line
+const DEFAULT_MEMORY_BLOCK_SIZE: u32 = 64;
+
+/// Number of blocks used if only `memory_block_size` is given
+const DEFAULT_MEMORY_BLOCKS: u32 = 512;
+
+/// Largest memory size selector of the `JENT_MAX_MEMSIZE_*` flags, 512 MiB
+const MAX_MEMSIZE: u32 = 15;
+
+/// Builder composing the `JENT_*` flags of a collector at runtime.
+///
+/// The default builder creates the same collector as `RandJitterEntropy::new`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RandJitterEntropyBuilder {
+    config: JitterConfig,
+    memory_blocks: Option<u32>,
+    memory_block_size: Option<u32>,
+}
+
+impl RandJitterEntropyBuilder {
+    /// Create builder with the default configuration of `RandJitterEntropy::new`.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn flag(mut self, flag: u32, enable: bool) -> Self {
+        if enable {
+            self.config.flags |= flag;
+        } else {
+            self.config.flags &= !flag;
+        }
+        self
+    }
+
+    /// Enforce FIPS 140 mode of the health tests (`JENT_FORCE_FIPS`).
+    #[must_use]
+    pub fn force_fips(self, enable: bool) -> Self {
+        self.flag(JENT_FORCE_FIPS, enable)
+    }
+
+    /// Request NTG.1 compliant operation (`JENT_NTG1`).
+    ///
+    /// Enabled by default with the `ntg1` feature.
+    #[must_use]
+    pub fn ntg1(self, enable: bool) -> Self {
+        self.flag(JENT_NTG1, enable)
+    }
+
+    /// Disable the memory access noise source (`JENT_DISABLE_MEMORY_ACCESS`).
+    #[must_use]
+    pub fn disable_memory_access(self, disable: bool) -> Self {
+        self.flag(JENT_DISABLE_MEMORY_ACCESS, disable)
+    }
+
+    /// Set the number of memory blocks accessed by the memory access noise source.
+    ///
+    /// Together with `memory_block_size` this bounds the memory of the collector via
+    /// the `JENT_MAX_MEMSIZE_*` flags. The library only supports powers of two between
+    /// 32 KiB and 512 MiB, so the size is rounded up to the next supported one. Larger
+    /// sizes harvest more cache timing noise on predictable CPUs, but slow down reads.
+    #[must_use]
+    pub fn memory_blocks(mut self, blocks: u32) -> Self {
+        self.memory_blocks = Some(blocks);
+        self
+    }
+
+    /// Set the size in bytes of a memory block, see `memory_blocks`.
+    #[must_use]
+    pub fn memory_block_size(mut self, size: u32) -> Self {
+        self.memory_block_size = Some(size);
+        self
+    }
+
+    /// `JENT_MAX_MEMSIZE_*` selector of the configured memory size, if any.
+    fn memsize(&self) -> Option<u32> {
+        if self.memory_blocks.is_none() && self.memory_block_size.is_none() {
+            return None;
+        }
+
+        let bytes = u64::from(self.memory_blocks.unwrap_or(DEFAULT_MEMORY_BLOCKS))
+            * u64::from(self.memory_block_size.unwrap_or(DEFAULT_MEMORY_BLOCK_SIZE));
+        let log2 = bytes.max(1).next_power_of_two().trailing_zeros();
+
+        Some(
+            log2.saturating_sub(JENT_MAX_MEMSIZE_OFFSET)
+                .clamp(1, MAX_MEMSIZE),
+        )
+    }
+
+    /// Select the time source of the collector.
+    #[must_use]
+    pub fn timer(mut self, timer: TimerMode) -> Self {
+        self.config.timer = timer;
+        self
+    }
+
+    /// Always use the internal timer thread (`JENT_FORCE_INTERNAL_TIMER`).
+    ///
+    /// Intended for platforms without a usable high-resolution CPU timer, where
+    /// initialization otherwise fails with `NoTime` or `CoarseTime`. See
+    /// `RandJitterEntropy::new_with_internal_timer` for the implications.
+    #[must_use]
+    pub fn force_internal_timer(self, enable: bool) -> Self {
+        self.timer(if enable {
+            TimerMode::ForceInternal
+        } else {
+            TimerMode::Auto
+        })
+    }
+
+    /// Set the oversampling rate.
+    #[must_use]
+    pub fn osr(mut self, osr: u32) -> Self {
+        self.config.osr = osr;
+        self
+    }
+
+    /// Set the size of the refill buffer, see `RandJitterEntropy::with_buffer_size`.
+    #[must_use]
+    pub fn block_size(mut self, block: usize) -> Self {
+        self.config.block_size = block;
+        self
+    }
+
+    /// Flags which are handed to jitterentropy.
+    #[must_use]
+    pub fn flags(&self) -> u32 {
+        self.config().effective_flags()
+    }
+
+    /// Configuration of the collector to be built.
+    #[must_use]
+    pub fn config(&self) -> JitterConfig {
+        let mut config = self.config;
+        if let Some(memsize) = self.memsize() {
+            config.flags &= !(MAX_MEMSIZE << JENT_FLAGS_TO_MEMSIZE_SHIFT);
+            config.flags |= memsize << JENT_FLAGS_TO_MEMSIZE_SHIFT;
+        }
+        config
+    }
+
+    /// Create the collector.
+    ///
+    /// # Errors
+    ///
+    /// Returns any error of `RandJitterEntropy::new_with_config`.
+    pub fn build(self) -> Result<RandJitterEntropy, JitterEntropyError> {
+        RandJitterEntropy::new_with_config(self.config())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand_core::TryRngCore;
+
+    #[test]
+    fn test_builder_default() {
+        let builder = RandJitterEntropyBuilder::new();
+        assert_eq!(builder.config(), JitterConfig::default());
+        assert_ne!(builder.flags() & JENT_FORCE_FIPS, 0);
+    }
+
+    #[test]
+    fn test_builder_flags() {
+        let builder = RandJitterEntropyBuilder::new();
+
+        let no_fips = builder.force_fips(false);
+        assert_eq!(no_fips.flags() & JENT_FORCE_FIPS, 0);
+        assert_eq!(no_fips.force_fips(true).flags(), builder.flags());
+
+        let no_mem = builder.disable_memory_access(true);
+        assert_ne!(no_mem.flags() & JENT_DISABLE_MEMORY_ACCESS, 0);
+        assert_eq!(no_mem.disable_memory_access(false).flags(), builder.flags());
+
+        assert_eq!(builder.osr(7).config().osr, 7);
+    }
+
+    #[test]
+    fn test_builder_internal_timer() {
+        use libjitterentropy_sys::jitterentropy::JENT_FORCE_INTERNAL_TIMER;
+
+        let builder = RandJitterEntropyBuilder::new().force_internal_timer(true);
+        assert_eq!(builder.config().timer, TimerMode::ForceInternal);
+        assert_ne!(builder.flags() & JENT_FORCE_INTERNAL_TIMER, 0);
+        assert_eq!(
+            builder.force_internal_timer(false).flags(),
+            RandJitterEntropyBuilder::new().flags()
+        );
+
+        // the library may be built without internal timer support
+        if let Ok(mut rng) = builder.build() {
+            assert!(rng.try_next_u64().is_ok());
+            rng.free();
+        }
+    }
+
+    #[test]
+    fn test_builder_ntg1() {
+        let builder = RandJitterEntropyBuilder::new();
+        assert_eq!(builder.ntg1(false).flags() & JENT_NTG1, 0);
+        assert_ne!(builder.ntg1(true).flags() & JENT_NTG1, 0);
+    }
+
+    #[test]
+    fn test_builder_memory() {
+        let memsize =
+            |builder: RandJitterEntropyBuilder| builder.flags() >> JENT_FLAGS_TO_MEMSIZE_SHIFT;
+        let builder = RandJitterEntropyBuilder::new();
+        assert_eq!(memsize(builder), 0);
+
+        // 128 KiB = 2^17 bytes
+        let large = builder.memory_blocks(1024).memory_block_size(128);
+        assert_eq!(memsize(large), 17 - JENT_MAX_MEMSIZE_OFFSET);
+        assert_eq!(
+            large.flags() & !(MAX_MEMSIZE << JENT_FLAGS_TO_MEMSIZE_SHIFT),
+            builder.flags()
+        );
+
+        // rounded up to the smallest and clamped to the largest supported size
+        assert_eq!(memsize(builder.memory_blocks(1)), 1);
+        assert_eq!(
+            memsize(builder.memory_blocks(u32::MAX).memory_block_size(u32::MAX)),
+            MAX_MEMSIZE
+        );
+
+        let mut rng = large.build().unwrap();
+        assert!(rng.try_next_u64().is_ok());
+    }
+
+    #[test]
+    fn test_builder_build() {
+        let mut rng = RandJitterEntropyBuilder::new()
+            .osr(4)
+            .disable_memory_access(true)
+            .build()
+            .unwrap();
+        assert!(rng.try_next_u64().is_ok());
+    }
+}
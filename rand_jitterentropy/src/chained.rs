@@ -0,0 +1,67 @@
+use crate::JitterEntropyError;
+use crate::resilient::ResilientRng;
+use rand_core::TryRngCore;
+
+/// Entropy source preferring jitterentropy and falling back to the kernel `getrandom`.
+///
+/// If jitterentropy cannot be initialized or fails a health test permanently, all
+/// further output is read from the operating system RNG instead. This is a
+/// `ResilientRng` switching on the first permanent failure.
+pub struct ChainedEntropy(ResilientRng);
+
+impl ChainedEntropy {
+    /// Create new source, using the kernel right away if jitterentropy is unavailable.
+    ///
+    /// # Errors
+    ///
+    /// Returns `OsRngFailed` if jitterentropy cannot be initialized and the kernel
+    /// source fails as well.
+    pub fn new() -> Result<Self, JitterEntropyError> {
+        ResilientRng::new_or_fallback(1).map(ChainedEntropy)
+    }
+
+    /// Returns `true` if output is read from the kernel source.
+    #[must_use]
+    pub fn is_fallback(&self) -> bool {
+        self.0.is_fallback()
+    }
+}
+
+impl TryRngCore for ChainedEntropy {
+    type Error = JitterEntropyError;
+
+    fn try_next_u32(&mut self) -> Result<u32, Self::Error> {
+        self.0.try_next_u32()
+    }
+
+    fn try_next_u64(&mut self) -> Result<u64, Self::Error> {
+        self.0.try_next_u64()
+    }
+
+    fn try_fill_bytes(&mut self, dst: &mut [u8]) -> Result<(), Self::Error> {
+        self.0.try_fill_bytes(dst)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chained_jitter() {
+        let mut rng = ChainedEntropy::new().unwrap();
+        assert!(!rng.is_fallback());
+        assert!(rng.try_next_u64().is_ok());
+    }
+
+    #[test]
+    fn test_chained_fallback() {
+        crate::tests::FAIL_NEXT_ALLOC.set(true);
+        let mut rng = ChainedEntropy::new().unwrap();
+        assert!(rng.is_fallback());
+
+        let mut b = [0u8; 32];
+        rng.try_fill_bytes(&mut b).unwrap();
+        assert_ne!(b, [0u8; 32]);
+    }
+}
@@ -0,0 +1,64 @@
+use libjitterentropy_sys::jitterentropy::{
+    JENT_DISABLE_INTERNAL_TIMER, JENT_FORCE_FIPS, JENT_FORCE_INTERNAL_TIMER,
+};
+
+/// Selects the time source of the entropy collector.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TimerMode {
+    /// Use the high-resolution CPU timer, falling back to the internal timer thread
+    #[default]
+    Auto,
+    /// Always use the internal timer thread
+    ForceInternal,
+    /// Never use the internal timer thread
+    DisableInternal,
+}
+
+/// Configuration of a `RandJitterEntropy` instance.
+///
+/// The `Default` matches the behavior of `RandJitterEntropy::new`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct JitterConfig {
+    /// Oversampling rate passed to jitterentropy
+    pub osr: u32,
+    /// `JENT_*` flags passed to jitterentropy, without timer flags
+    pub flags: u32,
+    /// Time source of the collector
+    pub timer: TimerMode,
+    /// Number of retries of a read after a transient health test failure
+    pub retries: u32,
+    /// Reallocate the collector after this many bytes of output
+    pub reseed_after_bytes: Option<u64>,
+    /// Size of the refill buffer serving `bytes` and `try_next_u32`, at least 1
+    pub block_size: usize,
+}
+
+impl JitterConfig {
+    /// Flags handed to jitterentropy, including the timer selection.
+    #[must_use]
+    pub fn effective_flags(&self) -> u32 {
+        match self.timer {
+            TimerMode::Auto => self.flags,
+            TimerMode::ForceInternal => self.flags | JENT_FORCE_INTERNAL_TIMER,
+            TimerMode::DisableInternal => self.flags | JENT_DISABLE_INTERNAL_TIMER,
+        }
+    }
+}
+
+impl Default for JitterConfig {
+    fn default() -> Self {
+        #[cfg(feature = "ntg1")]
+        let flags = JENT_FORCE_FIPS | libjitterentropy_sys::jitterentropy::JENT_NTG1;
+        #[cfg(not(feature = "ntg1"))]
+        let flags = JENT_FORCE_FIPS;
+
+        JitterConfig {
+            osr: 3,
+            flags,
+            timer: TimerMode::Auto,
+            retries: 0,
+            reseed_after_bytes: None,
+            block_size: 64,
+        }
+    }
+}
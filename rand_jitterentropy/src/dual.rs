@@ -0,0 +1,84 @@
+use crate::{JitterEntropyError, RandJitterEntropy};
+use rand_core::TryRngCore;
+
+/// Generator alternating between two independent jitterentropy collectors.
+///
+/// Each successive read is served by the other collector instance, so a
+/// transient fault in a single collector cannot dominate the output stream.
+pub struct DualJitter {
+    collectors: [RandJitterEntropy; 2],
+    next: usize,
+    reads: [u64; 2],
+}
+
+impl DualJitter {
+    /// Create new generator backed by two freshly allocated collectors.
+    ///
+    /// # Errors
+    ///
+    /// Returns any error of `RandJitterEntropy::new` for either collector.
+    pub fn new() -> Result<Self, JitterEntropyError> {
+        Ok(DualJitter {
+            collectors: [RandJitterEntropy::new()?, RandJitterEntropy::new()?],
+            next: 0,
+            reads: [0; 2],
+        })
+    }
+
+    /// Returns the collector serving the current read and advances to the other one.
+    fn collector(&mut self) -> &mut RandJitterEntropy {
+        let idx = self.next;
+        self.next ^= 1;
+        self.reads[idx] = self.reads[idx].wrapping_add(1);
+        &mut self.collectors[idx]
+    }
+}
+
+impl TryRngCore for DualJitter {
+    type Error = JitterEntropyError;
+
+    /// Generates a random u32 value from the next collector.
+    ///
+    /// # Errors
+    ///
+    /// Returns any runtime error of the collector serving this read.
+    fn try_next_u32(&mut self) -> Result<u32, Self::Error> {
+        self.collector().try_next_u32()
+    }
+
+    /// Generates a random u64 value from the next collector.
+    ///
+    /// # Errors
+    ///
+    /// Returns any runtime error of the collector serving this read.
+    fn try_next_u64(&mut self) -> Result<u64, Self::Error> {
+        self.collector().try_next_u64()
+    }
+
+    /// Fills the provided buffer with random bytes from the next collector.
+    ///
+    /// # Errors
+    ///
+    /// Returns any runtime error of the collector serving this read.
+    fn try_fill_bytes(&mut self, dst: &mut [u8]) -> Result<(), Self::Error> {
+        self.collector().try_fill_bytes(dst)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dual_alternates() {
+        let mut rng = DualJitter::new().unwrap();
+        for _ in 0..8 {
+            let mut b = [0u8; 32];
+            rng.try_fill_bytes(&mut b).unwrap();
+        }
+        assert_eq!(rng.reads, [4, 4]);
+
+        let _ = rng.try_next_u64().unwrap();
+        assert_eq!(rng.reads, [5, 4]);
+    }
+}
@@ -0,0 +1,79 @@
+use crate::{JitterEntropyError, RandJitterEntropy};
+use rand_core::{RngCore, TryRngCore};
+
+/// Infallible `RngCore` adapter for `RandJitterEntropy`.
+///
+/// Many APIs of the `rand` ecosystem require `RngCore`. This adapter provides it, but
+/// **panics** with the `JitterEntropyError` message if reading from the collector fails.
+/// Not available with the `no-panic` feature.
+pub struct JitterRng(RandJitterEntropy);
+
+impl JitterRng {
+    /// Create new adapter around a freshly allocated collector.
+    ///
+    /// # Errors
+    ///
+    /// Returns any error of `RandJitterEntropy::new`.
+    pub fn new() -> Result<Self, JitterEntropyError> {
+        Ok(JitterRng(RandJitterEntropy::new()?))
+    }
+
+    /// Returns the wrapped collector.
+    #[must_use]
+    pub fn into_inner(self) -> RandJitterEntropy {
+        self.0
+    }
+}
+
+impl RandJitterEntropy {
+    /// Convert into the infallible `RngCore` adapter `JitterRng`, which panics on errors.
+    #[must_use]
+    pub fn into_rng_core(self) -> JitterRng {
+        JitterRng(self)
+    }
+}
+
+impl RngCore for JitterRng {
+    /// # Panics
+    ///
+    /// Panics if entropy collection fails.
+    fn next_u32(&mut self) -> u32 {
+        self.0
+            .try_next_u32()
+            .unwrap_or_else(|e| panic!("jitterentropy failed: {e}"))
+    }
+
+    /// # Panics
+    ///
+    /// Panics if entropy collection fails.
+    fn next_u64(&mut self) -> u64 {
+        self.0
+            .try_next_u64()
+            .unwrap_or_else(|e| panic!("jitterentropy failed: {e}"))
+    }
+
+    /// # Panics
+    ///
+    /// Panics if entropy collection fails.
+    fn fill_bytes(&mut self, dst: &mut [u8]) {
+        self.0
+            .try_fill_bytes(dst)
+            .unwrap_or_else(|e| panic!("jitterentropy failed: {e}"));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rng_core() {
+        let mut rng = RandJitterEntropy::new().unwrap().into_rng_core();
+        let _ = rng.next_u32();
+        let _ = rng.next_u64();
+
+        let mut b = [0u8; 64];
+        rng.fill_bytes(&mut b);
+        assert_ne!(b, [0u8; 64]);
+    }
+}
@@ -0,0 +1,55 @@
+use crate::{JitterEntropyError, RandJitterEntropy};
+
+/// Iterator over random bytes, see `RandJitterEntropy::bytes`.
+///
+/// Bytes are served from the refill buffer of the generator. A failed refill yields the
+/// error, the next call tries again.
+pub struct Bytes<'a> {
+    rng: &'a mut RandJitterEntropy,
+}
+
+impl RandJitterEntropy {
+    /// Iterate over random bytes, e.g. for streaming consumers.
+    ///
+    /// The collector is only read once per block, see `with_buffer_size`.
+    pub fn bytes(&mut self) -> Bytes<'_> {
+        Bytes { rng: self }
+    }
+}
+
+impl Iterator for Bytes<'_> {
+    type Item = Result<u8, JitterEntropyError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut byte = [0u8; 1];
+        Some(self.rng.read_buffered(&mut byte).map(|()| byte[0]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand_core::TryRngCore;
+
+    #[test]
+    fn test_bytes() {
+        let mut rng = RandJitterEntropy::new().unwrap();
+        let bytes: Vec<u8> = rng.bytes().take(100).collect::<Result<_, _>>().unwrap();
+
+        assert_eq!(bytes.len(), 100);
+        assert_ne!(bytes, [0u8; 100]);
+    }
+
+    #[test]
+    fn test_block_sizes() {
+        for block in [1, 4096] {
+            let mut rng = RandJitterEntropy::with_buffer_size(3, block).unwrap();
+            let bytes: Vec<u8> = rng.bytes().take(100).collect::<Result<_, _>>().unwrap();
+            assert_eq!(bytes.len(), 100);
+            assert!(rng.try_next_u32().is_ok());
+        }
+
+        let rng = RandJitterEntropy::with_buffer_size(3, 0).unwrap();
+        assert_eq!(rng.buffer.len(), 1);
+    }
+}
@@ -0,0 +1,51 @@
+use crate::RandJitterEntropy;
+use linux_crng_ioctl::ioctl::add_randomness_to_kernel;
+use rand_core::TryRngCore;
+use zeroize::Zeroize;
+
+/// Reads `bytes` from a fresh jitterentropy collector and adds them to the kernel CRNG.
+///
+/// The data is credited with `ent_bits` of entropy. The intermediate buffer is zeroized
+/// after submission.
+///
+/// # Errors
+///
+/// Returns error if:
+/// - The collector cannot be allocated or reading from it fails
+/// - Any error of `add_randomness_to_kernel`, e.g. missing root privileges
+///
+/// # Example
+/// ```no_run
+/// # use rand_jitterentropy::kernel::pipe_jitter_to_kernel;
+/// # fn main() -> anyhow::Result<()> {
+/// pipe_jitter_to_kernel(64, 512)?;
+/// # Ok(())
+/// # }
+/// ```
+pub fn pipe_jitter_to_kernel(bytes: usize, ent_bits: u32) -> anyhow::Result<()> {
+    let mut rng = RandJitterEntropy::new()?;
+    let mut buf = vec![0u8; bytes];
+
+    let res = rng
+        .try_fill_bytes(&mut buf)
+        .map_err(anyhow::Error::from)
+        .and_then(|()| add_randomness_to_kernel(&buf, ent_bits).map_err(anyhow::Error::from));
+    buf.zeroize();
+
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pipe_jitter_to_kernel() {
+        if !nix::unistd::Uid::effective().is_root() {
+            println!("Skipping test: requires root privileges");
+            return;
+        }
+
+        assert!(pipe_jitter_to_kernel(256, 256 * 8).is_ok());
+    }
+}
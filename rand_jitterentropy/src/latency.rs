@@ -0,0 +1,170 @@
+use crate::{FILL_CHUNK_SIZE, JitterEntropyError, RandJitterEntropy};
+use rand_core::TryRngCore;
+use std::time::{Duration, Instant};
+use zeroize::Zeroize;
+
+/// Measurement window of `RandJitterEntropy::new_min_throughput`.
+const MIN_THROUGHPUT_WINDOW: Duration = Duration::from_millis(100);
+
+/// Distribution of read latencies, see `RandJitterEntropy::sample_latency_histogram`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LatencyHistogram {
+    samples: Vec<Duration>,
+}
+
+impl LatencyHistogram {
+    /// All measured read durations in ascending order.
+    #[must_use]
+    pub fn samples(&self) -> &[Duration] {
+        &self.samples
+    }
+
+    /// Latency below or equal to which `percent` percent of the reads completed.
+    #[must_use]
+    pub fn percentile(&self, percent: usize) -> Duration {
+        let rank = (self.samples.len() * percent.min(100)).div_ceil(100);
+        self.samples
+            .get(rank.saturating_sub(1))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// 50th percentile latency, see `percentile`.
+    #[must_use]
+    pub fn p50(&self) -> Duration {
+        self.percentile(50)
+    }
+
+    /// 95th percentile latency, see `percentile`.
+    #[must_use]
+    pub fn p95(&self) -> Duration {
+        self.percentile(95)
+    }
+
+    /// 99th percentile latency, see `percentile`.
+    #[must_use]
+    pub fn p99(&self) -> Duration {
+        self.percentile(99)
+    }
+}
+
+impl RandJitterEntropy {
+    /// Measure the latency of `reads` reads of `read_size` bytes each.
+    ///
+    /// Helps to characterize the jitter source of a host, e.g. to choose the
+    /// oversampling rate or a seed interval.
+    ///
+    /// # Errors
+    ///
+    /// Returns any runtime error of `try_fill_bytes`.
+    pub fn sample_latency_histogram(
+        &mut self,
+        reads: usize,
+        read_size: usize,
+    ) -> Result<LatencyHistogram, JitterEntropyError> {
+        let mut buf = vec![0u8; read_size];
+        let mut samples = Vec::with_capacity(reads);
+
+        for _ in 0..reads {
+            let start = Instant::now();
+            let res = self.try_fill_bytes(&mut buf);
+            samples.push(start.elapsed());
+            buf.zeroize();
+            res?;
+        }
+
+        samples.sort_unstable();
+        Ok(LatencyHistogram { samples })
+    }
+
+    /// Measure the output rate in bytes per second over `duration`.
+    ///
+    /// Reads of `buf_size` bytes are repeated until `duration` elapsed, at least once.
+    /// Allows to check whether the jitter source of a host meets a minimum throughput.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ProgErr` if `buf_size` exceeds `u32::MAX` and any runtime error of
+    /// `try_fill_bytes`.
+    pub fn measure_throughput(
+        &mut self,
+        duration: Duration,
+        buf_size: usize,
+    ) -> Result<f64, JitterEntropyError> {
+        let chunk = f64::from(u32::try_from(buf_size).map_err(|_| JitterEntropyError::ProgErr)?);
+        let mut buf = vec![0u8; buf_size];
+        let mut bytes = 0.0;
+
+        let start = Instant::now();
+        loop {
+            let res = self.try_fill_bytes(&mut buf);
+            buf.zeroize();
+            res?;
+            bytes += chunk;
+
+            let elapsed = start.elapsed();
+            if elapsed >= duration {
+                return Ok(bytes / elapsed.as_secs_f64());
+            }
+        }
+    }
+
+    /// Create new handle like `new`, which fails if it produces less than `min_bps` bytes
+    /// per second.
+    ///
+    /// The throughput is measured over a window of 100 ms right after construction.
+    /// Allows to reject a degraded jitter source on startup, e.g. on virtualized hosts,
+    /// instead of stalling under load.
+    ///
+    /// # Errors
+    ///
+    /// Returns `TooSlow` if the measured throughput is below `min_bps`, otherwise the
+    /// errors of `RandJitterEntropy::new` and `measure_throughput`.
+    pub fn new_min_throughput(min_bps: f64) -> Result<Self, JitterEntropyError> {
+        let mut rng = Self::new()?;
+        let rate = rng.measure_throughput(MIN_THROUGHPUT_WINDOW, FILL_CHUNK_SIZE)?;
+        if rate < min_bps {
+            warn!("jitterentropy throughput of {rate:.0} B/s is below {min_bps:.0} B/s");
+            return Err(JitterEntropyError::TooSlow);
+        }
+        Ok(rng)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_latency_histogram() {
+        let mut rng = RandJitterEntropy::new().unwrap();
+        let histogram = rng.sample_latency_histogram(50, 32).unwrap();
+
+        assert_eq!(histogram.samples().len(), 50);
+        assert!(histogram.p50() <= histogram.p95());
+        assert!(histogram.p95() <= histogram.p99());
+        assert!(histogram.p99() <= histogram.percentile(100));
+        assert_eq!(
+            histogram.percentile(100),
+            *histogram.samples().last().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_measure_throughput() {
+        let mut rng = RandJitterEntropy::new().unwrap();
+        let rate = rng
+            .measure_throughput(Duration::from_millis(200), 32)
+            .unwrap();
+        assert!(rate > 0.0);
+    }
+
+    #[test]
+    fn test_new_min_throughput() {
+        assert!(RandJitterEntropy::new_min_throughput(1.0).is_ok());
+        assert!(matches!(
+            RandJitterEntropy::new_min_throughput(f64::MAX),
+            Err(JitterEntropyError::TooSlow)
+        ));
+    }
+}
@@ -1,13 +1,207 @@
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+#![cfg_attr(
+    all(feature = "no-panic", not(test)),
+    deny(clippy::panic, clippy::unwrap_used, clippy::expect_used)
+)]
+
+extern crate alloc;
+
+use alloc::{boxed::Box, format, string::String, vec, vec::Vec};
+use core::sync::atomic::{AtomicPtr, Ordering};
+use libjitterentropy_sys::safe;
 use rand_core::TryRngCore;
-use std::sync::Mutex;
+#[cfg(not(feature = "std"))]
+use spin::{Mutex, MutexGuard};
+#[cfg(feature = "std")]
+use std::sync::{Mutex, MutexGuard, PoisonError};
+use zeroize::{Zeroize, Zeroizing};
+
+/// Forwards to `log::debug!` with the `log` feature, compiled out otherwise.
+macro_rules! debug {
+    ($($arg:tt)*) => {{
+        #[cfg(feature = "log")]
+        log::debug!($($arg)*);
+        #[cfg(not(feature = "log"))]
+        let _ = format_args!($($arg)*);
+    }};
+}
+
+/// Forwards to `log::warn!` with the `log` feature, compiled out otherwise.
+macro_rules! warn {
+    ($($arg:tt)*) => {{
+        #[cfg(feature = "log")]
+        log::warn!($($arg)*);
+        #[cfg(not(feature = "log"))]
+        let _ = format_args!($($arg)*);
+    }};
+}
+
+/// Generator alternating between two collectors
+pub mod dual;
+
+/// Pool of pre-allocated collectors
+#[cfg(feature = "std")]
+pub mod pool;
+
+/// Collector configuration
+pub mod config;
+
+/// Generator with fallback to the kernel RNG
+#[cfg(feature = "std")]
+pub mod resilient;
+
+/// Generator falling back to the kernel RNG on unavailability
+#[cfg(feature = "std")]
+pub mod chained;
+
+/// Builder for collector flags
+pub mod builder;
+
+/// Read latency measurement
+#[cfg(feature = "std")]
+pub mod latency;
+
+/// Diagnostic report of a collector
+pub mod report;
+
+/// Byte iterator
+pub mod iter;
+
+/// Infallible `RngCore` adapter
+#[cfg(not(feature = "no-panic"))]
+pub mod infallible;
+
+/// Feeding the Linux kernel CRNG
+#[cfg(feature = "kernel")]
+pub mod kernel;
+
+pub use builder::RandJitterEntropyBuilder;
+#[cfg(feature = "std")]
+pub use chained::ChainedEntropy;
+pub use config::{JitterConfig, TimerMode};
+pub use dual::DualJitter;
+#[cfg(not(feature = "no-panic"))]
+pub use infallible::JitterRng;
+pub use iter::Bytes;
+#[cfg(feature = "std")]
+pub use latency::LatencyHistogram;
+#[cfg(feature = "std")]
+pub use pool::{JitterEntropyPool, PooledJitter};
+pub use report::{EntropyEstimateReport, HealthStats};
+#[cfg(feature = "std")]
+pub use resilient::ResilientRng;
+
+/// Global library state, `jent_entropy_init_ex` is only run for the first instance.
+struct LibState {
+    instances: u32,
+    fips_initialized: bool,
+}
+
+static LIB_MUTEX_UNPRIV: Mutex<LibState> = Mutex::new(LibState {
+    instances: 0,
+    fips_initialized: false,
+});
+
+/// Locks the global library state, failing if a panicking thread poisoned it.
+#[cfg(feature = "std")]
+fn lock_state() -> Result<MutexGuard<'static, LibState>, JitterEntropyError> {
+    lock_checked(&LIB_MUTEX_UNPRIV)
+}
+
+/// Locks `mutex`, reporting poisoning as `LockPoisoned`.
+#[cfg(feature = "std")]
+fn lock_checked<T>(mutex: &Mutex<T>) -> Result<MutexGuard<'_, T>, JitterEntropyError> {
+    mutex.lock().map_err(|_| JitterEntropyError::LockPoisoned)
+}
+
+/// Locks the global library state, a spin lock cannot be poisoned.
+#[cfg(not(feature = "std"))]
+#[allow(clippy::unnecessary_wraps)]
+fn lock_state() -> Result<MutexGuard<'static, LibState>, JitterEntropyError> {
+    Ok(LIB_MUTEX_UNPRIV.lock())
+}
+
+/// Locks the global library state, ignoring poisoning.
+#[cfg(feature = "std")]
+fn lock_state_unpoisoned() -> MutexGuard<'static, LibState> {
+    LIB_MUTEX_UNPRIV
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Locks the global library state.
+#[cfg(not(feature = "std"))]
+fn lock_state_unpoisoned() -> MutexGuard<'static, LibState> {
+    LIB_MUTEX_UNPRIV.lock()
+}
+
+/// User callback of `RandJitterEntropy::on_fips_failure`, NULL if none is registered.
+static FIPS_FAILURE_CALLBACK: AtomicPtr<()> = AtomicPtr::new(core::ptr::null_mut());
 
-static LIB_MUTEX_UNPRIV: Mutex<u32> = Mutex::new(0u32);
+/// Invoked by jitterentropy on permanent health test failures, forwards to the user callback.
+unsafe extern "C" fn fips_failure_trampoline(
+    _ec: *mut libjitterentropy_sys::jitterentropy::rand_data,
+    health_failure: core::ffi::c_uint,
+) {
+    warn!("jitterentropy permanent health test failure {health_failure:#x}");
+
+    let callback = FIPS_FAILURE_CALLBACK.load(Ordering::Acquire);
+    if !callback.is_null() {
+        // SAFETY: only `on_fips_failure` stores into `FIPS_FAILURE_CALLBACK`, always a `fn()`
+        let callback = unsafe { core::mem::transmute::<*mut (), fn()>(callback) };
+        callback();
+    }
+}
+
+/// Granularity of `RandJitterEntropy::try_fill_bytes_deadline` and
+/// `RandJitterEntropy::try_fill_bytes_partial`, one jitterentropy output block.
+pub const FILL_CHUNK_SIZE: usize = 32;
+
+/// Id of the current process, used to detect collectors inherited across `fork`.
+#[cfg(feature = "std")]
+fn current_pid() -> u32 {
+    std::process::id()
+}
+
+/// Without `std` there is no process to fork, so all collectors share one id.
+#[cfg(not(feature = "std"))]
+fn current_pid() -> u32 {
+    0
+}
 
 pub struct RandJitterEntropy {
     rand_data: *mut libjitterentropy_sys::jitterentropy::rand_data,
+    pid: u32,
+    config: JitterConfig,
+    bytes_since_reseed: u64,
+    bytes_generated: u64,
+    last_health_failure: Option<JitterEntropyError>,
+    buffer: Zeroizing<Vec<u8>>,
+    buffer_pos: usize,
+    observer: Option<Box<dyn FnMut(ReadStats) + Send>>,
 }
 
+/// Statistics of a single `try_fill_bytes` call, see `RandJitterEntropy::set_read_observer`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ReadStats {
+    /// Number of requested bytes
+    pub bytes: usize,
+    /// Wall-clock duration of the read, zero without the `std` feature
+    pub duration: core::time::Duration,
+    /// Number of retries after transient health test failures
+    pub retries: u32,
+    /// Number of times the collector was reallocated during the read
+    pub reinstantiations: u32,
+}
+
+// SAFETY: every instance owns its own collector allocated by `jent_entropy_collector_alloc`
+// and jitterentropy keeps no thread-local state tied to it. Only the one-time library init
+// is global, which is guarded by `LIB_MUTEX_UNPRIV`. `Sync` is deliberately not
+// implemented, as reading entropy requires exclusive access to the collector.
+unsafe impl Send for RandJitterEntropy {}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(i32)]
 /// Represents all possible errors that can occur during Jitter RNG operations.
 ///
 /// This enum covers both initialization errors and runtime errors that may occur
@@ -34,6 +228,9 @@ pub enum JitterEntropyError {
     /// RCT failed during initialization
     Rct = 10,
     /// Hash self test failed
+    ///
+    /// With the `openssl` feature, the SHA3 implementation is provided by `libcrypto`
+    /// and this error reports a failure of its known-answer test.
     Hash = 11,
     /// Can't allocate memory for initialization
     Memory = 12,
@@ -55,6 +252,18 @@ pub enum JitterEntropyError {
     AptPermanentFailure = -7,
     /// LAG permanent failure (unrecoverable)
     LagPermanentFailure = -8,
+    /// Collector was created in another process and not reinitialized after `fork`
+    ForkDetected = -100,
+    /// Kernel CRNG did not become ready in time
+    KernelNotReady = -101,
+    /// Operating system RNG failed
+    OsRngFailed = -102,
+    /// Oversampling rate must be at least 1
+    InvalidOsr = -103,
+    /// Measured throughput is below the required minimum
+    TooSlow = -104,
+    /// A thread panicked while holding the global library lock
+    LockPoisoned = -105,
 }
 
 impl JitterEntropyError {
@@ -126,10 +335,40 @@ impl JitterEntropyError {
             _ => Err(Self::ProgErr), // Unknown errors treated as programming errors
         }
     }
+
+    /// Returns the raw error code of this error.
+    ///
+    /// For errors reported by jitterentropy this is the C error code, so that
+    /// `from_c_code(e.as_raw()) == Err(e)` holds.
+    #[must_use]
+    pub fn as_raw(&self) -> i32 {
+        *self as i32
+    }
+
+    /// Returns `true` for unrecoverable health test failures, which require a new collector.
+    #[must_use]
+    pub fn is_permanent(&self) -> bool {
+        matches!(
+            self,
+            Self::RctPermanentFailure | Self::AptPermanentFailure | Self::LagPermanentFailure
+        )
+    }
+
+    /// Returns `true` for intermittent health test failures, which allow to simply read again.
+    #[must_use]
+    pub fn is_transient(&self) -> bool {
+        matches!(self, Self::RctFailed | Self::AptFailed | Self::LagFailed)
+    }
+
+    /// Returns `true` for errors reported during initialization (positive C error codes).
+    #[must_use]
+    pub fn is_init_error(&self) -> bool {
+        self.as_raw() > 0
+    }
 }
 
-impl std::fmt::Display for JitterEntropyError {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl core::fmt::Display for JitterEntropyError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         match self {
             Self::NoTime => write!(f, "Timer service not available"),
             Self::CoarseTime => write!(f, "Timer too coarse for RNG"),
@@ -152,15 +391,72 @@ impl std::fmt::Display for JitterEntropyError {
             Self::RctPermanentFailure => write!(f, "RCT permanent failure"),
             Self::AptPermanentFailure => write!(f, "APT permanent failure"),
             Self::LagPermanentFailure => write!(f, "LAG permanent failure"),
+            Self::ForkDetected => write!(f, "Collector used across fork without reinit"),
+            Self::KernelNotReady => write!(f, "Kernel CRNG not ready"),
+            Self::OsRngFailed => write!(f, "Operating system RNG failed"),
+            Self::InvalidOsr => write!(f, "Oversampling rate must be at least 1"),
+            Self::TooSlow => write!(f, "Throughput below required minimum"),
+            Self::LockPoisoned => write!(f, "Library lock poisoned by a panicking thread"),
         }
     }
 }
 
-impl std::error::Error for JitterEntropyError {}
+impl core::error::Error for JitterEntropyError {}
+
+/// `rand_core` 0.9 has no error type of its own, generic code bounds `TryRngCore::Error`
+/// by `core::error::Error` and bridges to `std::io::Error` where a concrete type is needed.
+#[cfg(feature = "std")]
+impl From<JitterEntropyError> for std::io::Error {
+    /// Wraps the error as `ErrorKind::Other`.
+    fn from(err: JitterEntropyError) -> Self {
+        std::io::Error::other(err)
+    }
+}
+
+#[cfg(feature = "std")]
+impl JitterEntropyError {
+    /// Recovers the variant from an `std::io::Error` created by the `From` conversion.
+    ///
+    /// Returns `None` for all other I/O errors.
+    #[must_use]
+    pub fn from_io_error(err: &std::io::Error) -> Option<Self> {
+        err.get_ref()?.downcast_ref::<Self>().copied()
+    }
+}
+
+/// Error of `RandJitterEntropy::try_fill_bytes_partial` with the progress before the failure.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FillError {
+    /// Number of bytes at the start of the buffer filled before the failure
+    pub bytes_written: usize,
+    /// Cause of the failure
+    pub error: JitterEntropyError,
+}
+
+impl core::fmt::Display for FillError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{} after {} bytes", self.error, self.bytes_written)
+    }
+}
+
+impl core::error::Error for FillError {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+impl From<FillError> for JitterEntropyError {
+    fn from(err: FillError) -> Self {
+        err.error
+    }
+}
 
 impl From<i32> for JitterEntropyError {
+    /// Converts a non-zero C error code, the success code 0 maps to `ProgErr`.
     fn from(code: i32) -> Self {
-        JitterEntropyError::from_c_code(code).unwrap_err()
+        JitterEntropyError::from_c_code(code)
+            .err()
+            .unwrap_or(JitterEntropyError::ProgErr)
     }
 }
 
@@ -191,242 +487,1597 @@ impl RandJitterEntropy {
     /// - `AptPermanentFailure` - Permanent APT failure
     /// - `LagPermanentFailure` - Permanent LAG failure
     /// - `ProgErr` - Programming or internal error
+    /// - `LockPoisoned` - A thread panicked while holding the global library lock
     pub fn new() -> Result<Self, JitterEntropyError> {
-        let mut guard = LIB_MUTEX_UNPRIV
-            .lock()
-            .map_err(|_| JitterEntropyError::ProgErr)?;
-
-        let osr: std::os::raw::c_uint = 3;
-        #[cfg(feature = "ntg1")]
-        let flags: std::os::raw::c_uint = libjitterentropy_sys::jitterentropy::JENT_FORCE_FIPS | libjitterentropy_sys::jitterentropy::JENT_NTG1;
-        #[cfg(not(feature = "ntg1"))]
-        let flags: std::os::raw::c_uint = libjitterentropy_sys::jitterentropy::JENT_FORCE_FIPS;
-
-        let ret = if *guard == 0 {
-            unsafe {
-                JitterEntropyError::from_c_code(
-                    libjitterentropy_sys::jitterentropy::jent_entropy_init_ex(osr, flags),
-                )?;
-            };
-            true
-        } else {
-            true
-        };
-
-        if ret {
-            *guard += 1;
-        } else {
-            return Err(JitterEntropyError::ProgErr);
-        }
+        Self::new_with_config(JitterConfig::default())
+    }
 
-        let rand_data = unsafe {
-            libjitterentropy_sys::jitterentropy::jent_entropy_collector_alloc(osr, flags)
-        };
-        if rand_data.is_null() {
-            Err(JitterEntropyError::NullCollector)
-        } else {
-            Ok(RandJitterEntropy { rand_data })
-        }
+    /// Create new handle for jitterentropy based True RNG without enforcing FIPS mode.
+    ///
+    /// Omits `JENT_FORCE_FIPS`, which avoids the heavier FIPS health test path. The global
+    /// jitterentropy self-tests only run once for the first instance; if that instance
+    /// already ran them in FIPS mode, they are not repeated for non-FIPS instances.
+    ///
+    /// # Errors
+    ///
+    /// Initialization can fail with the same errors as `RandJitterEntropy::new`.
+    pub fn new_non_fips() -> Result<Self, JitterEntropyError> {
+        RandJitterEntropyBuilder::new().force_fips(false).build()
     }
-}
 
-impl TryRngCore for RandJitterEntropy {
-    type Error = JitterEntropyError;
+    /// Create new handle for jitterentropy based True RNG with oversampling rate `osr`.
+    ///
+    /// Higher rates increase the entropy gathered per output bit at the cost of
+    /// throughput. `new` uses an oversampling rate of 3.
+    ///
+    /// # Errors
+    ///
+    /// Returns `InvalidOsr` if `osr` is 0, otherwise the errors of `RandJitterEntropy::new`.
+    pub fn with_osr(osr: u32) -> Result<Self, JitterEntropyError> {
+        Self::new_with_config(JitterConfig {
+            osr,
+            ..JitterConfig::default()
+        })
+    }
 
-    /// Generates a random u32 value.
+    /// Create new handle with oversampling rate `osr` and a refill buffer of `block` bytes.
+    ///
+    /// The buffer serves small reads of `bytes` and `try_next_u32`. Larger blocks amortize
+    /// the overhead of reading the collector, but increase the latency of the read that
+    /// refills the buffer. Buffered bytes are wiped on drop. A `block` of 0 is treated as 1.
     ///
     /// # Errors
     ///
-    /// Returns error if:
-    /// - Entropy collection fails (any runtime error from `JitterEntropyError`)
-    /// - `ProgErr` if internal type conversion fails
-    fn try_next_u32(&mut self) -> Result<u32, Self::Error> {
-        u32::try_from(self.try_next_u64()? & 0xFF_FF_FF_FF).map_err(|_| JitterEntropyError::ProgErr)
+    /// Returns `InvalidOsr` if `osr` is 0, otherwise the errors of `RandJitterEntropy::new`.
+    pub fn with_buffer_size(osr: u32, block: usize) -> Result<Self, JitterEntropyError> {
+        Self::new_with_config(JitterConfig {
+            osr,
+            block_size: block,
+            ..JitterConfig::default()
+        })
     }
 
-    /// Generates a random u64 value.
+    /// Create new handle using the internal timer thread instead of the CPU timer.
+    ///
+    /// Fallback for platforms without a high-resolution clock, e.g. minimal containers,
+    /// where `new` fails with `NoTime` or `CoarseTime`.
+    ///
+    /// In this mode every collector owns a background thread incrementing a shared
+    /// counter, which occupies a CPU while the collector is alive. The thread only
+    /// accesses memory of its own collector and is stopped and joined when the
+    /// collector is freed by `free` or `Drop`, so moving the instance across threads
+    /// stays sound.
     ///
     /// # Errors
     ///
-    /// Returns error if entropy collection fails with any runtime error from `JitterEntropyError`
-    fn try_next_u64(&mut self) -> Result<u64, Self::Error> {
-        let mut bytes: [u8; 8] = [0; 8];
-        self.try_fill_bytes(&mut bytes)?;
+    /// Returns the same errors as `RandJitterEntropy::new`, in particular if the linked
+    /// library was built without internal timer support.
+    pub fn new_with_internal_timer() -> Result<Self, JitterEntropyError> {
+        Self::new_with_config(JitterConfig {
+            timer: TimerMode::ForceInternal,
+            ..JitterConfig::default()
+        })
+    }
 
-        Ok(u64::from_ne_bytes(bytes))
+    /// Create new handle, retrying with the internal timer thread if `new` fails.
+    #[cfg(not(feature = "no-panic"))]
+    fn new_or_internal_timer() -> Result<Self, JitterEntropyError> {
+        Self::new().or_else(|e| {
+            warn!("jitterentropy initialization failed: {e}, retrying with internal timer");
+            Self::new_with_internal_timer()
+        })
     }
 
-    /// Fills the provided buffer with random bytes.
+    /// Create new handle for jitterentropy based True RNG from `config`.
     ///
     /// # Errors
     ///
-    /// Returns error if:
-    /// - Entropy collection fails (any runtime error from `JitterEntropyError`)
-    /// - `ProgErr` if buffer length conversion fails
-    /// - Runtime health test failures (`RctFailed`, `AptFailed`, `LagFailed`)
-    /// - Permanent test failures (`RctPermanentFailure`, `AptPermanentFailure`, `LagPermanentFailure`)
-    fn try_fill_bytes(&mut self, dst: &mut [u8]) -> Result<(), Self::Error> {
-        let ret = unsafe {
-            libjitterentropy_sys::jitterentropy::jent_read_entropy_safe(
-                &mut self.rand_data,
-                dst.as_mut_ptr().cast(),
-                dst.len(),
-            )
-        };
+    /// Returns `InvalidOsr` if `config.osr` is 0, otherwise initialization can fail with
+    /// the same errors as `RandJitterEntropy::new`.
+    pub fn new_with_config(config: JitterConfig) -> Result<Self, JitterEntropyError> {
+        if config.osr == 0 {
+            return Err(JitterEntropyError::InvalidOsr);
+        }
+
+        let mut guard = lock_state()?;
 
-        let expected_len = isize::try_from(dst.len()).map_err(|_| JitterEntropyError::ProgErr)?;
+        let osr: core::ffi::c_uint = config.osr;
+        let flags: core::ffi::c_uint = config.effective_flags();
 
-        if ret == expected_len {
-            Ok(())
-        } else {
-            Err(JitterEntropyError::from_c_code(
-                i32::try_from(ret).map_err(|_| JitterEntropyError::ProgErr)?,
-            )
-            .unwrap_err())
+        // The global self-tests run once for the first instance with its flags. A FIPS
+        // instance created after a non-FIPS init reruns them in FIPS mode, while a non-FIPS
+        // instance is fine with the stricter FIPS self-tests.
+        let fips = flags & libjitterentropy_sys::jitterentropy::JENT_FORCE_FIPS != 0;
+        if guard.instances == 0 || (fips && !guard.fips_initialized) {
+            safe::entropy_init_ex(osr, flags)
+                .map_err(JitterEntropyError::from)
+                .inspect_err(|e| warn!("jitterentropy initialization failed: {e}"))?;
+            guard.fips_initialized = fips;
+            debug!("jitterentropy initialized with osr {osr}, flags {flags:#x}");
         }
-    }
-}
 
-impl Default for RandJitterEntropy {
-    fn default() -> Self {
-        Self::new().unwrap()
-    }
-}
+        // Only account the instance once nothing can fail anymore, every counted instance
+        // has to be matched by exactly one `release`.
+        let instances = guard
+            .instances
+            .checked_add(1)
+            .ok_or(JitterEntropyError::ProgErr)?;
+        let rand_data = alloc_collector(osr, flags)?;
+        guard.instances = instances;
 
-impl Drop for RandJitterEntropy {
-    fn drop(&mut self) {
-        unsafe {
-            libjitterentropy_sys::jitterentropy::jent_entropy_collector_free(self.rand_data);
-        }
+        Ok(RandJitterEntropy {
+            rand_data,
+            pid: current_pid(),
+            config,
+            bytes_since_reseed: 0,
+            bytes_generated: 0,
+            last_health_failure: None,
+            buffer: Zeroizing::new(vec![0; config.block_size.max(1)]),
+            buffer_pos: config.block_size.max(1),
+            observer: None,
+        })
+    }
 
-        let mut guard = LIB_MUTEX_UNPRIV.lock().unwrap();
+    /// Run the global jitterentropy self-tests without allocating a collector.
+    ///
+    /// jitterentropy only exports its self-tests as part of `jent_entropy_init_ex`, which
+    /// covers the timer checks, the hash known-answer test and the GCD self-test. They are
+    /// run with the flags of `RandJitterEntropy::new`, existing collectors are unaffected.
+    /// Allows monitoring tools to check the health of the entropy source.
+    ///
+    /// # Errors
+    ///
+    /// Returns the initialization errors of `RandJitterEntropy::new`, e.g. `Hash` or `Gcd`.
+    pub fn run_self_tests() -> Result<(), JitterEntropyError> {
+        let config = JitterConfig::default();
+        let _guard = lock_state()?;
 
-        *guard -= 1;
+        safe::entropy_init_ex(config.osr, config.effective_flags())
+            .map_err(JitterEntropyError::from)
+            .inspect_err(|e| warn!("jitterentropy self-tests failed: {e}"))
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    /// Replaces the collector with a freshly allocated one of the same configuration.
+    fn reseed(&mut self) -> Result<(), JitterEntropyError> {
+        let rand_data = alloc_collector(self.config.osr, self.config.effective_flags())?;
 
-    #[test]
-    fn test_error_codes() {
-        assert_eq!(JitterEntropyError::from_c_code(0), Ok(()));
-        assert_eq!(
-            JitterEntropyError::from_c_code(1),
-            Err(JitterEntropyError::NoTime)
-        );
-        assert_eq!(
-            JitterEntropyError::from_c_code(13),
-            Err(JitterEntropyError::Gcd)
-        );
-        assert_eq!(
-            JitterEntropyError::from_c_code(-1),
-            Err(JitterEntropyError::NullCollector)
-        );
-        assert_eq!(
-            JitterEntropyError::from_c_code(-8),
-            Err(JitterEntropyError::LagPermanentFailure)
-        );
-        assert_eq!(
-            JitterEntropyError::from_c_code(99),
-            Err(JitterEntropyError::ProgErr)
-        );
+        unsafe { safe::entropy_collector_free(self.rand_data) };
+        self.rand_data = rand_data;
+        self.bytes_since_reseed = 0;
+        debug!("jitterentropy collector reseeded");
+
+        Ok(())
     }
 
-    #[test]
-    fn test_error_display() {
-        assert_eq!(
-            JitterEntropyError::NoTime.to_string(),
-            "Timer service not available"
-        );
-        assert_eq!(
-            JitterEntropyError::NullCollector.to_string(),
-            "Entropy collector is NULL"
-        );
-        assert_eq!(
-            JitterEntropyError::RctPermanentFailure.to_string(),
-            "RCT permanent failure"
-        );
+    /// Replace the collector in place, e.g. to recover from a permanent health test failure.
+    ///
+    /// The fresh collector uses the oversampling rate and flags this instance was created
+    /// with. It is allocated before the current one is freed, so on error the instance
+    /// keeps its current collector.
+    ///
+    /// # Errors
+    ///
+    /// Returns `NullCollector` if the collector cannot be allocated.
+    pub fn reinit(&mut self) -> Result<(), JitterEntropyError> {
+        self.reseed()
     }
 
-    #[test]
-    fn test_from_i32() {
-        let err: JitterEntropyError = (-1).into();
-        assert_eq!(err, JitterEntropyError::NullCollector);
+    /// Fill `dst` from the refill buffer, reading a new block from the collector when empty.
+    fn read_buffered(&mut self, dst: &mut [u8]) -> Result<(), JitterEntropyError> {
+        // buffered output of the parent must not be served to a forked child
+        if self.pid != current_pid() {
+            return Err(JitterEntropyError::ForkDetected);
+        }
 
-        let err: JitterEntropyError = (-8).into();
-        assert_eq!(err, JitterEntropyError::LagPermanentFailure);
-    }
+        for byte in dst {
+            if self.buffer_pos == self.buffer.len() {
+                let mut buffer = core::mem::take(&mut self.buffer);
+                let res = self.try_fill_bytes(&mut buffer);
+                self.buffer = buffer;
+                res?;
+                self.buffer_pos = 0;
+            }
 
-    #[test]
-    fn test_u32() {
-        let mut rng = RandJitterEntropy::new().unwrap();
-        for _ in 0..128 {
-            let u = rng.try_next_u32();
-            assert!(u.is_ok());
+            *byte = self.buffer[self.buffer_pos];
+            self.buffer[self.buffer_pos] = 0;
+            self.buffer_pos += 1;
         }
+
+        Ok(())
     }
 
-    #[test]
-    fn test_u64() {
-        let mut rng = RandJitterEntropy::new().unwrap();
-        for _ in 0..128 {
-            let u = rng.try_next_u64();
-            assert!(u.is_ok());
-        }
+    /// Single read from the collector without retries.
+    fn read_entropy(&mut self, dst: &mut [u8]) -> Result<(), JitterEntropyError> {
+        unsafe { safe::read_entropy_safe(&mut self.rand_data, dst) }
+            .map_err(JitterEntropyError::from)
     }
 
-    #[test]
-    fn test_speed() {
-        use std::time::Instant;
-        let start = Instant::now();
-        let mut num_bytes = 0usize;
-        let mut rng = RandJitterEntropy::new().unwrap();
+    /// Read and discard `bytes` bytes of output, intended right after construction.
+    ///
+    /// This is optional hardening only: jitterentropy already runs its startup health
+    /// tests during construction, so the first output is fit for use.
+    ///
+    /// # Errors
+    ///
+    /// Returns any runtime error of `try_fill_bytes`.
+    pub fn warmup(&mut self, bytes: usize) -> Result<(), JitterEntropyError> {
+        let mut scratch = [0u8; 64];
+        let mut remaining = bytes;
 
-        loop {
-            let mut b = [0u8; 32];
-            rng.try_fill_bytes(&mut b).unwrap();
+        while remaining > 0 {
+            let len = remaining.min(scratch.len());
+            let res = self.try_fill_bytes(&mut scratch[..len]);
+            scratch.zeroize();
+            res?;
+            remaining -= len;
+        }
 
-            let now = Instant::now();
+        Ok(())
+    }
 
-            num_bytes += b.len();
+    /// Fill `dst`, reading again after transient health test failures.
+    ///
+    /// At most `max_attempts` reads are performed, at least one. Permanent failures and all
+    /// other errors are returned immediately.
+    ///
+    /// # Errors
+    ///
+    /// Returns the last error of `try_fill_bytes` once the attempts are exhausted, or the
+    /// first error which is not transient.
+    pub fn try_fill_bytes_retry(
+        &mut self,
+        dst: &mut [u8],
+        max_attempts: u32,
+    ) -> Result<(), JitterEntropyError> {
+        retry_transient(max_attempts, || self.try_fill_bytes(dst))
+    }
 
-            if (now - start).as_secs() > 2 {
-                let datarate = f64::from(u32::try_from(num_bytes).unwrap())
-                    / (now - start).as_secs_f64()
-                    / 1024.0;
-                println!("datarate: {datarate} KiB/s");
-                break;
-            }
-        }
+    /// Collect `N` bytes of conditioned entropy into an array, e.g. to seed a `SeedableRng`.
+    ///
+    /// The array is filled in place without intermediate copies. Wiping the returned seed,
+    /// e.g. by wrapping it in `zeroize::Zeroizing`, is up to the caller.
+    ///
+    /// # Errors
+    ///
+    /// Returns any runtime error of `try_fill_bytes`.
+    pub fn fill_seed<const N: usize>(&mut self) -> Result<[u8; N], JitterEntropyError> {
+        let mut seed = [0u8; N];
+        self.try_fill_bytes(&mut seed)?;
+        Ok(seed)
     }
 
-    #[test]
-    fn test_bytes() {
-        let mut rng = RandJitterEntropy::new().unwrap();
+    /// Generates a random u128 value, e.g. for UUIDs or nonces.
+    ///
+    /// Uses native byte order like `try_next_u64`. The intermediate buffer is zeroized.
+    ///
+    /// # Errors
+    ///
+    /// Returns any runtime error of `try_fill_bytes`.
+    pub fn try_next_u128(&mut self) -> Result<u128, JitterEntropyError> {
+        let mut bytes = Zeroizing::new([0u8; 16]);
+        self.try_fill_bytes(bytes.as_mut())?;
 
-        for buffer_size in 0..=256 {
-            let mut buffer = vec![0u8; buffer_size];
-            assert!(rng.try_fill_bytes(&mut buffer).is_ok());
-            println!("{buffer_size}: {buffer:#04X?}");
+        Ok(u128::from_ne_bytes(*bytes))
+    }
+
+    /// Fills `dst` like `try_fill_bytes`, reporting the progress on failure.
+    ///
+    /// The buffer is filled in chunks of `FILL_CHUNK_SIZE` bytes. If a chunk fails, the
+    /// returned `FillError` tells how many bytes at the start of `dst` were filled, so the
+    /// caller can retry only the remainder.
+    ///
+    /// # Errors
+    ///
+    /// Returns any runtime error of `try_fill_bytes` together with the filled length.
+    pub fn try_fill_bytes_partial(&mut self, dst: &mut [u8]) -> Result<(), FillError> {
+        let mut bytes_written = 0;
+        for chunk in dst.chunks_mut(FILL_CHUNK_SIZE) {
+            self.try_fill_bytes(chunk).map_err(|error| FillError {
+                bytes_written,
+                error,
+            })?;
+            bytes_written += chunk.len();
         }
+        Ok(())
     }
 
-    #[test]
-    fn test_multi_instantiation() {
-        for _ in 0..256 {
-            let mut rng = RandJitterEntropy::new().unwrap();
-            let u = rng.try_next_u32().unwrap();
-            println!("Got {u}");
+    /// Fills `dst` until `deadline`, returns the number of filled bytes.
+    ///
+    /// The buffer is filled in chunks of `FILL_CHUNK_SIZE` bytes and the deadline is
+    /// checked before each chunk. A running collection cannot be preempted, so the call
+    /// may overrun the deadline by the duration of one chunk. If the deadline passes
+    /// mid-fill, fewer than `dst.len()` bytes are filled and the caller may continue with
+    /// the remainder.
+    ///
+    /// # Errors
+    ///
+    /// Returns any runtime error of `try_fill_bytes`.
+    #[cfg(feature = "std")]
+    pub fn try_fill_bytes_deadline(
+        &mut self,
+        dst: &mut [u8],
+        deadline: std::time::Instant,
+    ) -> Result<usize, JitterEntropyError> {
+        let mut filled = 0;
+        for chunk in dst.chunks_mut(FILL_CHUNK_SIZE) {
+            if std::time::Instant::now() >= deadline {
+                debug!("deadline passed after {filled} of {} bytes", dst.len());
+                break;
+            }
+            self.try_fill_bytes(chunk)?;
+            filled += chunk.len();
         }
+        Ok(filled)
     }
 
-    #[test]
-    fn test_multi_threading() {
-        let mut threads = vec![];
-        let mut rng = RandJitterEntropy::new().unwrap();
-        let _ = rng.try_next_u64().unwrap();
+    /// Fills `out` with random `u32` values in a single read of the collector.
+    ///
+    /// The random bytes are written directly into `out` and interpreted in native byte
+    /// order like `try_next_u32`, so the same collector output yields different values on
+    /// little- and big-endian hosts.
+    ///
+    /// # Errors
+    ///
+    /// Returns any runtime error of `try_fill_bytes`.
+    pub fn try_fill_u32(&mut self, out: &mut [u32]) -> Result<(), JitterEntropyError> {
+        // SAFETY: u8 has no alignment requirement and every bit pattern is a valid u32
+        let bytes = unsafe {
+            core::slice::from_raw_parts_mut(out.as_mut_ptr().cast::<u8>(), size_of_val(out))
+        };
+        self.try_fill_bytes(bytes)
+    }
+
+    /// Fills `out` with random `u64` values in a single read of the collector.
+    ///
+    /// Native byte order applies like for `try_fill_u32`.
+    ///
+    /// # Errors
+    ///
+    /// Returns any runtime error of `try_fill_bytes`.
+    pub fn try_fill_u64(&mut self, out: &mut [u64]) -> Result<(), JitterEntropyError> {
+        // SAFETY: u8 has no alignment requirement and every bit pattern is a valid u64
+        let bytes = unsafe {
+            core::slice::from_raw_parts_mut(out.as_mut_ptr().cast::<u8>(), size_of_val(out))
+        };
+        self.try_fill_bytes(bytes)
+    }
+
+    /// Fills `dst` on the blocking thread pool of Tokio, see `try_fill_bytes`.
+    ///
+    /// The collector moves into the blocking task and back into `self` once the task
+    /// finished, so the async runtime is never blocked by entropy collection. If the
+    /// returned future is dropped before completion, the collector is released by the
+    /// task and all further reads from `self` fail with `NullCollector`.
+    ///
+    /// # Errors
+    ///
+    /// Returns any runtime error of `try_fill_bytes` and `ProgErr` if the blocking task
+    /// panicked or was cancelled.
+    #[cfg(feature = "async")]
+    pub async fn fill_bytes_async(&mut self, dst: &mut [u8]) -> Result<(), JitterEntropyError> {
+        let detached = self.detached();
+        let mut rng = core::mem::replace(self, detached);
+        let len = dst.len();
+
+        let (rng, res) = tokio::task::spawn_blocking(move || {
+            let mut buf = Zeroizing::new(vec![0u8; len]);
+            let res = rng.try_fill_bytes(&mut buf).map(|()| buf);
+            (rng, res)
+        })
+        .await
+        .map_err(|_| JitterEntropyError::ProgErr)?;
+
+        *self = rng;
+        dst.copy_from_slice(&res?);
+        Ok(())
+    }
+
+    /// Instance without collector, standing in for `self` while the collector is moved.
+    #[cfg(feature = "async")]
+    fn detached(&self) -> Self {
+        RandJitterEntropy {
+            rand_data: core::ptr::null_mut(),
+            pid: self.pid,
+            config: self.config,
+            bytes_since_reseed: 0,
+            bytes_generated: 0,
+            last_health_failure: None,
+            buffer: Zeroizing::new(Vec::new()),
+            buffer_pos: 0,
+            observer: None,
+        }
+    }
+
+    /// Oversampling rate the collector was created with.
+    #[must_use]
+    pub fn osr(&self) -> u32 {
+        self.config.osr
+    }
+
+    /// Entropy in bits credited to a single time delta of the collector.
+    ///
+    /// jitterentropy provides no query for this. Its entropy assessment assumes at least
+    /// `1 / osr` bits per time delta, which is why it gathers `osr` deltas for every
+    /// conditioned output bit. The estimate therefore lies in `(0.0, 1.0]`.
+    #[must_use]
+    pub fn entropy_per_bit_estimate(&self) -> f64 {
+        1.0 / f64::from(self.config.osr)
+    }
+
+    /// `JENT_*` flags the collector was created with, including the timer selection.
+    #[must_use]
+    pub fn flags(&self) -> u32 {
+        self.config.effective_flags()
+    }
+
+    /// Returns `true` if the collector runs on the internal timer thread.
+    ///
+    /// Derived from the timer selection the collector was created with. jitterentropy
+    /// exports no query whether it fell back to the internal timer itself, so such a
+    /// fallback in `TimerMode::Auto` is not reported.
+    #[must_use]
+    pub fn uses_internal_timer(&self) -> bool {
+        self.flags() & libjitterentropy_sys::jitterentropy::JENT_FORCE_INTERNAL_TIMER != 0
+    }
+
+    /// Full configuration the collector was created with.
+    #[must_use]
+    pub fn config(&self) -> JitterConfig {
+        self.config
+    }
+
+    /// Returns the version of the linked jitterentropy library as encoded by `jent_version`.
+    #[must_use]
+    pub fn library_version() -> u32 {
+        safe::version()
+    }
+
+    /// Returns the version of the linked jitterentropy library as `major.minor.patch`.
+    #[must_use]
+    pub fn library_version_string() -> String {
+        let version = Self::library_version();
+        format!(
+            "{}.{}.{}",
+            version / 1_000_000,
+            version / 10_000 % 100,
+            version / 100 % 100
+        )
+    }
+
+    /// Replace the collector inherited from the parent process after `fork`.
+    ///
+    /// A forked child shares the parent's collector state and would produce output
+    /// correlated with the parent. `try_fill_bytes` therefore fails with `ForkDetected`
+    /// in the child until this method allocated a fresh collector.
+    ///
+    /// Must not be called while another thread of the parent held the library mutex
+    /// at the time of `fork`, as the child would then block forever.
+    ///
+    /// # Errors
+    ///
+    /// Returns any error of `RandJitterEntropy::new`.
+    pub fn reinit_after_fork(&mut self) -> Result<(), JitterEntropyError> {
+        let observer = self.observer.take();
+        *self = Self::new_with_config(self.config)?;
+        self.observer = observer;
+        Ok(())
+    }
+
+    /// Register `callback`, which is invoked on permanent health test failures.
+    ///
+    /// Allows to halt the application or raise an alarm right when jitterentropy detects
+    /// the failure, instead of only on the error of the next read. The registration is
+    /// global: it applies to all collectors of the process and replaces a previously
+    /// registered callback. The callback runs inside the failing read, a panic aborts the
+    /// process as it cannot unwind through jitterentropy.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ProgErr` if jitterentropy rejects the registration.
+    pub fn on_fips_failure(callback: fn()) -> Result<(), JitterEntropyError> {
+        FIPS_FAILURE_CALLBACK.store(callback as *mut (), Ordering::Release);
+        safe::set_fips_failure_callback(Some(fips_failure_trampoline))
+            .map_err(|_| JitterEntropyError::ProgErr)
+    }
+
+    /// Register `observer`, which is invoked with the statistics of every read.
+    ///
+    /// The observer is called at the end of each `try_fill_bytes`, also for failed reads.
+    pub fn set_read_observer<F>(&mut self, observer: F)
+    where
+        F: FnMut(ReadStats) + Send + 'static,
+    {
+        self.observer = Some(Box::new(observer));
+    }
+
+    /// Reads from the collector with retries and reseeding, accounting into `stats`.
+    fn fill_with_stats(
+        &mut self,
+        dst: &mut [u8],
+        stats: &mut ReadStats,
+    ) -> Result<(), JitterEntropyError> {
+        if self.pid != current_pid() {
+            return Err(JitterEntropyError::ForkDetected);
+        }
+
+        loop {
+            let rand_data = self.rand_data;
+            let res = self.read_entropy(dst);
+            // jent_read_entropy_safe reallocates the collector with a higher oversampling
+            // rate on intermittent health test failures, permanent ones are returned
+            if self.rand_data != rand_data {
+                stats.reinstantiations += 1;
+                debug!("jitterentropy collector reinstantiated");
+            }
+            if let Err(e) = res
+                && (e.is_transient() || e.is_permanent())
+            {
+                warn!("jitterentropy health test failed: {e}");
+                self.last_health_failure = Some(e);
+            }
+
+            match res {
+                Ok(()) => break,
+                Err(e) if e.is_transient() && stats.retries < self.config.retries => {
+                    stats.retries += 1;
+                    debug!("Retrying read after {e}, retry {}", stats.retries);
+                }
+                Err(e) => return Err(e),
+            }
+        }
+
+        let len = u64::try_from(dst.len()).map_err(|_| JitterEntropyError::ProgErr)?;
+        self.bytes_generated = self.bytes_generated.saturating_add(len);
+
+        if let Some(limit) = self.config.reseed_after_bytes {
+            self.bytes_since_reseed = self.bytes_since_reseed.saturating_add(len);
+            if self.bytes_since_reseed >= limit {
+                self.reseed()?;
+                stats.reinstantiations += 1;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Fill `dst` once the kernel CRNG is seeded.
+    ///
+    /// Intended for early-boot code, which must not produce output before the system
+    /// gathered some entropy. Polls the kernel readiness until `timeout` elapsed, a
+    /// timeout too large to represent as a deadline waits forever.
+    ///
+    /// # Errors
+    ///
+    /// Returns `KernelNotReady` if the kernel CRNG is not ready within `timeout` or
+    /// its readiness cannot be queried, otherwise any error of `try_fill_bytes`.
+    #[cfg(feature = "kernel")]
+    pub fn fill_bytes_after_kernel_ready(
+        &mut self,
+        dst: &mut [u8],
+        timeout: std::time::Duration,
+    ) -> Result<(), JitterEntropyError> {
+        let deadline = std::time::Instant::now().checked_add(timeout);
+
+        loop {
+            match linux_crng_ioctl::proc::crng_ready() {
+                Ok(true) => break,
+                Ok(false) if deadline.is_none_or(|d| std::time::Instant::now() < d) => {
+                    std::thread::sleep(std::time::Duration::from_millis(10));
+                }
+                _ => return Err(JitterEntropyError::KernelNotReady),
+            }
+        }
+
+        self.try_fill_bytes(dst)
+    }
+
+    /// Read `n` random bytes and feed them directly into `hasher`.
+    ///
+    /// Output is read in small chunks, so the full amount is never held in memory at once.
+    ///
+    /// # Errors
+    ///
+    /// Returns any runtime error of `try_fill_bytes`.
+    #[cfg(feature = "digest")]
+    pub fn read_into_hasher<D: digest::Digest>(
+        &mut self,
+        hasher: &mut D,
+        n: usize,
+    ) -> Result<(), JitterEntropyError> {
+        let mut chunk = [0u8; 64];
+        let mut remaining = n;
+
+        while remaining > 0 {
+            let len = remaining.min(chunk.len());
+            let res = self.try_fill_bytes(&mut chunk[..len]);
+            if res.is_ok() {
+                hasher.update(&chunk[..len]);
+            }
+            chunk.fill(0);
+            res?;
+            remaining -= len;
+        }
+
+        Ok(())
+    }
+
+    /// Read directly via `jent_read_entropy` for entropy assessment, e.g. SP800-90B testing.
+    ///
+    /// Bypasses all recovery of `try_fill_bytes`: no retries, no reseeding and no
+    /// reinstantiation of the collector on health test failures. Returns the number of
+    /// bytes produced. Note that the library still conditions this output, the raw noise
+    /// samples are only reachable via its test interfaces.
+    ///
+    /// The output is NOT suitable for direct cryptographic use.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ForkDetected` in a forked child and the runtime error of `jent_read_entropy`
+    /// otherwise.
+    #[cfg(feature = "raw-entropy")]
+    pub fn read_raw_entropy(&mut self, dst: &mut [u8]) -> Result<usize, JitterEntropyError> {
+        if self.pid != current_pid() {
+            return Err(JitterEntropyError::ForkDetected);
+        }
+
+        unsafe { safe::read_entropy(self.rand_data, dst) }.map_err(JitterEntropyError::from)
+    }
+
+    /// Free the collector immediately instead of relying on `Drop`.
+    ///
+    /// Consuming `self` guarantees the collector cannot be used afterwards. Callers
+    /// managing resources deterministically, e.g. releasing all collectors before
+    /// `fork`, should prefer this over implicit drop order, so that no child process
+    /// inherits a live collector state shared with its parent.
+    pub fn free(mut self) {
+        self.release();
+    }
+
+    /// Frees the collector and decrements the instance counter exactly once.
+    fn release(&mut self) {
+        if self.rand_data.is_null() {
+            return;
+        }
+
+        unsafe { safe::entropy_collector_free(self.rand_data) };
+        self.rand_data = core::ptr::null_mut();
+
+        let mut guard = lock_state_unpoisoned();
+        guard.instances = guard.instances.saturating_sub(1);
+    }
+}
+
+/// Allocates a collector, tests can force a failure via `tests::FAIL_NEXT_ALLOC`.
+fn alloc_collector(
+    osr: u32,
+    flags: u32,
+) -> Result<*mut libjitterentropy_sys::jitterentropy::rand_data, JitterEntropyError> {
+    #[cfg(test)]
+    if tests::FAIL_NEXT_ALLOC.take() {
+        return Err(JitterEntropyError::NullCollector);
+    }
+
+    safe::entropy_collector_alloc(osr, flags).map_err(JitterEntropyError::from)
+}
+
+/// Calls `read` until it succeeds, fails with a non-transient error or `max_attempts` is hit.
+fn retry_transient<F>(max_attempts: u32, mut read: F) -> Result<(), JitterEntropyError>
+where
+    F: FnMut() -> Result<(), JitterEntropyError>,
+{
+    let mut attempts = 1;
+    loop {
+        match read() {
+            Err(e) if e.is_transient() && attempts < max_attempts => {
+                attempts += 1;
+                debug!("Retrying read after {e}, attempt {attempts} of {max_attempts}");
+            }
+            res => return res,
+        }
+    }
+}
+
+impl TryRngCore for RandJitterEntropy {
+    type Error = JitterEntropyError;
+
+    /// Generates a random u32 value.
+    ///
+    /// Takes 4 bytes from the refill buffer in native byte order like `try_next_u64`.
+    /// The intermediate buffer is zeroized.
+    ///
+    /// # Errors
+    ///
+    /// Returns error if entropy collection fails with any runtime error from `JitterEntropyError`
+    fn try_next_u32(&mut self) -> Result<u32, Self::Error> {
+        let mut bytes = Zeroizing::new([0u8; 4]);
+        self.read_buffered(bytes.as_mut())?;
+
+        Ok(u32::from_ne_bytes(*bytes))
+    }
+
+    /// Generates a random u64 value.
+    ///
+    /// The intermediate buffer is zeroized, the returned value itself cannot be wiped by
+    /// this crate and is the responsibility of the caller.
+    ///
+    /// # Errors
+    ///
+    /// Returns error if entropy collection fails with any runtime error from `JitterEntropyError`
+    fn try_next_u64(&mut self) -> Result<u64, Self::Error> {
+        let mut bytes = Zeroizing::new([0u8; 8]);
+        self.try_fill_bytes(bytes.as_mut())?;
+
+        Ok(u64::from_ne_bytes(*bytes))
+    }
+
+    /// Fills the provided buffer with random bytes.
+    ///
+    /// # Errors
+    ///
+    /// Returns error if:
+    /// - Entropy collection fails (any runtime error from `JitterEntropyError`)
+    /// - `ProgErr` if buffer length conversion fails
+    /// - Runtime health test failures (`RctFailed`, `AptFailed`, `LagFailed`)
+    /// - Permanent test failures (`RctPermanentFailure`, `AptPermanentFailure`, `LagPermanentFailure`)
+    /// - `ForkDetected` if used in a forked child before `reinit_after_fork`
+    fn try_fill_bytes(&mut self, dst: &mut [u8]) -> Result<(), Self::Error> {
+        #[cfg(feature = "std")]
+        let start = std::time::Instant::now();
+        let mut stats = ReadStats {
+            bytes: dst.len(),
+            ..ReadStats::default()
+        };
+
+        #[cfg(feature = "tracing")]
+        let span = tracing::debug_span!(
+            "try_fill_bytes",
+            len = dst.len(),
+            elapsed_us = tracing::field::Empty,
+            result = tracing::field::Empty
+        )
+        .entered();
+
+        let res = self.fill_with_stats(dst, &mut stats);
+
+        #[cfg(feature = "tracing")]
+        {
+            let elapsed_us = u64::try_from(start.elapsed().as_micros()).unwrap_or(u64::MAX);
+            span.record("elapsed_us", elapsed_us);
+            match &res {
+                Ok(()) => span.record("result", "ok"),
+                Err(e) => span.record("result", tracing::field::display(e)),
+            };
+            tracing::debug!(elapsed_us, ok = res.is_ok(), "jitterentropy read finished");
+        }
+
+        if let Some(observer) = &mut self.observer {
+            #[cfg(feature = "std")]
+            {
+                stats.duration = start.elapsed();
+            }
+            observer(stats);
+        }
+
+        res
+    }
+}
+
+/// Allows to pass `&mut rng` where a `TryRngCore` is expected without giving up ownership.
+///
+/// `rand_core` only provides this for `RngCore` implementors, so it is forwarded here.
+impl TryRngCore for &mut RandJitterEntropy {
+    type Error = JitterEntropyError;
+
+    fn try_next_u32(&mut self) -> Result<u32, Self::Error> {
+        (**self).try_next_u32()
+    }
+
+    fn try_next_u64(&mut self) -> Result<u64, Self::Error> {
+        (**self).try_next_u64()
+    }
+
+    fn try_fill_bytes(&mut self, dst: &mut [u8]) -> Result<(), Self::Error> {
+        (**self).try_fill_bytes(dst)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::io::Read for RandJitterEntropy {
+    /// Fills all of `buf` with random bytes.
+    ///
+    /// # Errors
+    ///
+    /// Returns any runtime error of `try_fill_bytes` as `ErrorKind::Other`.
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        self.try_fill_bytes(buf)?;
+        Ok(buf.len())
+    }
+}
+
+/// Creates a collector like `RandJitterEntropy::new`, falling back to
+/// `RandJitterEntropy::new_with_internal_timer` if that fails, e.g. on platforms with a
+/// coarse CPU timer.
+///
+/// Not available with the `no-panic` feature, use the fallible constructors instead.
+///
+/// # Panics
+///
+/// Panics if both constructors fail.
+#[cfg(not(feature = "no-panic"))]
+impl Default for RandJitterEntropy {
+    fn default() -> Self {
+        Self::new_or_internal_timer().expect("jitterentropy initialization failed")
+    }
+}
+
+impl Drop for RandJitterEntropy {
+    fn drop(&mut self) {
+        self.release();
+    }
+}
+
+impl core::fmt::Debug for RandJitterEntropy {
+    /// Prints the configuration, the address of the collector is redacted.
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("RandJitterEntropy")
+            .field("osr", &self.config.osr)
+            .field("flags", &format_args!("{:#x}", self.flags()))
+            .field("collector", &format_args!("<opaque>"))
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    std::thread_local! {
+        /// Makes the next collector allocation of the current thread fail.
+        pub(crate) static FAIL_NEXT_ALLOC: Cell<bool> = const { Cell::new(false) };
+    }
+
+    #[cfg(not(feature = "no-panic"))]
+    #[test]
+    fn test_default_internal_timer_fallback() {
+        assert_eq!(RandJitterEntropy::default().config().timer, TimerMode::Auto);
+
+        FAIL_NEXT_ALLOC.set(true);
+        // the library may be built without internal timer support
+        if let Ok(rng) = RandJitterEntropy::new_or_internal_timer() {
+            assert_eq!(rng.config().timer, TimerMode::ForceInternal);
+        }
+        assert!(!FAIL_NEXT_ALLOC.get());
+    }
+
+    #[test]
+    fn test_failed_alloc_not_counted() {
+        const FAILURES: u32 = 10_000;
+
+        // keeps the library initialized, so the failing constructions stay cheap
+        let _rng = RandJitterEntropy::new().unwrap();
+
+        for _ in 0..FAILURES {
+            FAIL_NEXT_ALLOC.set(true);
+            assert_eq!(
+                RandJitterEntropy::new().err(),
+                Some(JitterEntropyError::NullCollector)
+            );
+        }
+
+        // other tests running concurrently hold far fewer instances than leaked counts
+        assert!(lock_state_unpoisoned().instances < FAILURES);
+    }
+
+    #[test]
+    fn test_error_codes() {
+        assert_eq!(JitterEntropyError::from_c_code(0), Ok(()));
+        assert_eq!(
+            JitterEntropyError::from_c_code(1),
+            Err(JitterEntropyError::NoTime)
+        );
+        assert_eq!(
+            JitterEntropyError::from_c_code(13),
+            Err(JitterEntropyError::Gcd)
+        );
+        assert_eq!(
+            JitterEntropyError::from_c_code(-1),
+            Err(JitterEntropyError::NullCollector)
+        );
+        assert_eq!(
+            JitterEntropyError::from_c_code(-8),
+            Err(JitterEntropyError::LagPermanentFailure)
+        );
+        assert_eq!(
+            JitterEntropyError::from_c_code(99),
+            Err(JitterEntropyError::ProgErr)
+        );
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_lock_poisoned() {
+        // poisoning the global lock would fail concurrent tests, so a local one is used
+        let mutex = std::sync::Arc::new(Mutex::new(0));
+        let poisoner = std::sync::Arc::clone(&mutex);
+        let _ = std::thread::spawn(move || {
+            let _guard = poisoner.lock().unwrap();
+            panic!("poison the lock");
+        })
+        .join();
+
+        assert!(matches!(
+            lock_checked(&mutex),
+            Err(JitterEntropyError::LockPoisoned)
+        ));
+        assert!(lock_checked(&Mutex::new(0)).is_ok());
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_global_lock_poisoned() {
+        // poisoning the global lock would fail concurrent tests, so this test reruns
+        // itself alone in a child process which poisons the lock
+        if std::env::var_os("RAND_JITTERENTROPY_POISON_LOCK").is_some() {
+            let _ = std::thread::spawn(|| {
+                let _state = lock_state_unpoisoned();
+                panic!("poison the lock");
+            })
+            .join();
+
+            assert!(matches!(
+                RandJitterEntropy::new(),
+                Err(JitterEntropyError::LockPoisoned)
+            ));
+            return;
+        }
+
+        let output = std::process::Command::new(std::env::current_exe().unwrap())
+            .args(["--exact", "tests::test_global_lock_poisoned"])
+            .env("RAND_JITTERENTROPY_POISON_LOCK", "1")
+            .output()
+            .unwrap();
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(output.status.success(), "{stdout}");
+        assert!(stdout.contains("1 passed"), "{stdout}");
+    }
+
+    #[test]
+    fn test_uses_internal_timer() {
+        assert!(!RandJitterEntropy::new().unwrap().uses_internal_timer());
+
+        // the library may be built without internal timer support
+        if let Ok(rng) = RandJitterEntropy::new_with_internal_timer() {
+            assert!(rng.uses_internal_timer());
+        }
+    }
+
+    #[test]
+    fn test_debug_redacts_collector() {
+        let rng = RandJitterEntropy::new().unwrap();
+        let debug = format!("{rng:?}");
+
+        assert!(debug.starts_with("RandJitterEntropy { osr: 3, flags: 0x"));
+        assert!(debug.ends_with("collector: <opaque> }"));
+        assert!(!debug.contains(&format!("{:p}", rng.rand_data)));
+    }
+
+    #[test]
+    fn test_error_display() {
+        assert_eq!(
+            JitterEntropyError::NoTime.to_string(),
+            "Timer service not available"
+        );
+        assert_eq!(
+            JitterEntropyError::NullCollector.to_string(),
+            "Entropy collector is NULL"
+        );
+        assert_eq!(
+            JitterEntropyError::RctPermanentFailure.to_string(),
+            "RCT permanent failure"
+        );
+    }
+
+    const C_ERRORS: [JitterEntropyError; 21] = [
+        JitterEntropyError::NoTime,
+        JitterEntropyError::CoarseTime,
+        JitterEntropyError::NoMonotonic,
+        JitterEntropyError::MinVariation,
+        JitterEntropyError::VarVar,
+        JitterEntropyError::MinVarVar,
+        JitterEntropyError::ProgErr,
+        JitterEntropyError::Stuck,
+        JitterEntropyError::Health,
+        JitterEntropyError::Rct,
+        JitterEntropyError::Hash,
+        JitterEntropyError::Memory,
+        JitterEntropyError::Gcd,
+        JitterEntropyError::NullCollector,
+        JitterEntropyError::RctFailed,
+        JitterEntropyError::AptFailed,
+        JitterEntropyError::TimerInitFailed,
+        JitterEntropyError::LagFailed,
+        JitterEntropyError::RctPermanentFailure,
+        JitterEntropyError::AptPermanentFailure,
+        JitterEntropyError::LagPermanentFailure,
+    ];
+
+    #[test]
+    fn test_error_round_trip() {
+        let mut messages = std::collections::HashSet::new();
+
+        for err in C_ERRORS {
+            assert_eq!(JitterEntropyError::from_c_code(err.as_raw()), Err(err));
+
+            let msg = err.to_string();
+            assert!(!msg.is_empty());
+            assert!(messages.insert(msg), "duplicate message for {err:?}");
+        }
+
+        let sweep = (-1000..=1000).chain([i32::MIN, i32::MAX]);
+        for code in sweep {
+            match JitterEntropyError::from_c_code(code) {
+                Ok(()) => assert_eq!(code, 0),
+                Err(err) if C_ERRORS.iter().any(|e| e.as_raw() == code) => {
+                    assert_eq!(err.as_raw(), code);
+                }
+                Err(err) => assert_eq!(err, JitterEntropyError::ProgErr),
+            }
+        }
+    }
+
+    #[test]
+    fn test_error_categories() {
+        for err in C_ERRORS {
+            let categories = [err.is_permanent(), err.is_transient(), err.is_init_error()];
+            assert!(categories.iter().filter(|c| **c).count() <= 1);
+        }
+
+        assert!(JitterEntropyError::RctPermanentFailure.is_permanent());
+        assert!(JitterEntropyError::LagPermanentFailure.is_permanent());
+        assert!(!JitterEntropyError::RctFailed.is_permanent());
+
+        assert!(JitterEntropyError::AptFailed.is_transient());
+        assert!(JitterEntropyError::LagFailed.is_transient());
+        assert!(!JitterEntropyError::AptPermanentFailure.is_transient());
+
+        assert!(JitterEntropyError::NoTime.is_init_error());
+        assert!(JitterEntropyError::Gcd.is_init_error());
+        assert!(!JitterEntropyError::NullCollector.is_init_error());
+        assert!(!JitterEntropyError::ForkDetected.is_init_error());
+    }
+
+    #[test]
+    fn test_from_i32() {
+        let err: JitterEntropyError = 0.into();
+        assert_eq!(err, JitterEntropyError::ProgErr);
+
+        let err: JitterEntropyError = (-1).into();
+        assert_eq!(err, JitterEntropyError::NullCollector);
+
+        let err: JitterEntropyError = (-8).into();
+        assert_eq!(err, JitterEntropyError::LagPermanentFailure);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_into_io_error() {
+        fn read() -> std::io::Result<()> {
+            Err(JitterEntropyError::RctFailed)?
+        }
+
+        let err = read().unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::Other);
+        assert_eq!(err.to_string(), JitterEntropyError::RctFailed.to_string());
+        let inner = err.into_inner().unwrap();
+        assert_eq!(
+            inner.downcast_ref::<JitterEntropyError>(),
+            Some(&JitterEntropyError::RctFailed)
+        );
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_error_conversion_keeps_variant() {
+        fn generic<E: Into<std::io::Error>>(err: E) -> std::io::Error {
+            err.into()
+        }
+
+        let err = generic(JitterEntropyError::AptPermanentFailure);
+        assert_eq!(
+            JitterEntropyError::from_io_error(&err),
+            Some(JitterEntropyError::AptPermanentFailure)
+        );
+        assert_eq!(
+            JitterEntropyError::from_io_error(&std::io::Error::other("other")),
+            None
+        );
+
+        let boxed: Box<dyn core::error::Error + Send + Sync> =
+            JitterEntropyError::AptPermanentFailure.into();
+        assert_eq!(boxed.to_string(), "APT permanent failure");
+        assert!(
+            boxed
+                .downcast_ref::<JitterEntropyError>()
+                .unwrap()
+                .is_permanent()
+        );
+    }
+
+    #[cfg(feature = "log")]
+    #[test]
+    fn test_log_retry() {
+        use std::sync::Mutex;
+
+        struct CapturingLogger(Mutex<Vec<String>>);
+
+        impl log::Log for CapturingLogger {
+            fn enabled(&self, _: &log::Metadata) -> bool {
+                true
+            }
+
+            fn log(&self, record: &log::Record) {
+                self.0.lock().unwrap().push(record.args().to_string());
+            }
+
+            fn flush(&self) {}
+        }
+
+        static LOGGER: CapturingLogger = CapturingLogger(Mutex::new(Vec::new()));
+        log::set_logger(&LOGGER).unwrap();
+        log::set_max_level(log::LevelFilter::Trace);
+
+        let res = retry_transient(2, || Err(JitterEntropyError::AptFailed));
+        assert_eq!(res, Err(JitterEntropyError::AptFailed));
+
+        let messages = LOGGER.0.lock().unwrap();
+        assert!(messages.iter().any(|m| m.contains("Retrying read after")));
+    }
+
+    #[test]
+    fn test_u32() {
+        let mut rng = RandJitterEntropy::new().unwrap();
+        for _ in 0..128 {
+            let u = rng.try_next_u32();
+            assert!(u.is_ok());
+        }
+    }
+
+    #[test]
+    fn test_u64() {
+        let mut rng = RandJitterEntropy::new().unwrap();
+        for _ in 0..128 {
+            let u = rng.try_next_u64();
+            assert!(u.is_ok());
+        }
+    }
+
+    #[test]
+    fn test_next_int_read_sizes() {
+        use std::sync::{Arc, Mutex};
+
+        let sizes = Arc::new(Mutex::new(Vec::new()));
+        let mut rng = RandJitterEntropy::new().unwrap();
+        let observed = Arc::clone(&sizes);
+        rng.set_read_observer(move |stats| observed.lock().unwrap().push(stats.bytes));
+
+        let _ = rng.try_next_u32().unwrap();
+        let _ = rng.try_next_u32().unwrap();
+        let _ = rng.try_next_u64().unwrap();
+        let _ = rng.try_next_u128().unwrap();
+
+        // u32 values are served from one refill block, wider integers are read directly
+        assert_eq!(*sizes.lock().unwrap(), [64, 8, 16]);
+    }
+
+    #[test]
+    fn test_u128() {
+        let mut rng = RandJitterEntropy::new().unwrap();
+        for _ in 0..128 {
+            let u = rng.try_next_u128();
+            assert!(u.is_ok());
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_fill_deadline() {
+        use std::time::{Duration, Instant};
+
+        let mut rng = RandJitterEntropy::new().unwrap();
+        let mut buf = [0u8; 100];
+
+        let deadline = Instant::now() + Duration::from_secs(60);
+        assert_eq!(
+            rng.try_fill_bytes_deadline(&mut buf, deadline),
+            Ok(buf.len())
+        );
+        assert_eq!(rng.try_fill_bytes_deadline(&mut buf, Instant::now()), Ok(0));
+    }
+
+    #[test]
+    fn test_fill_partial() {
+        let mut rng = RandJitterEntropy::new().unwrap();
+        let mut buf = [0u8; 100];
+        assert_eq!(rng.try_fill_bytes_partial(&mut buf), Ok(()));
+
+        let err = FillError {
+            bytes_written: 64,
+            error: JitterEntropyError::RctFailed,
+        };
+        assert_eq!(JitterEntropyError::from(err), JitterEntropyError::RctFailed);
+    }
+
+    #[cfg(feature = "tracing")]
+    #[test]
+    #[tracing_test::traced_test]
+    fn test_fill_span() {
+        let mut rng = RandJitterEntropy::new().unwrap();
+        let mut buf = [0u8; 32];
+        rng.try_fill_bytes(&mut buf).unwrap();
+
+        assert!(logs_contain("try_fill_bytes{len=32"));
+        assert!(logs_contain("ok=true"));
+    }
+
+    #[test]
+    fn test_fill_integers() {
+        let mut rng = RandJitterEntropy::new().unwrap();
+
+        let mut words = [0u32; 256];
+        rng.try_fill_u32(&mut words).unwrap();
+        assert!(words.iter().any(|&w| w != 0));
+
+        let mut words = [0u64; 256];
+        rng.try_fill_u64(&mut words).unwrap();
+        assert!(words.iter().any(|&w| w != 0));
+
+        assert_eq!(rng.try_fill_u64(&mut []), Ok(()));
+    }
+
+    #[cfg(feature = "async")]
+    #[tokio::test]
+    async fn test_fill_bytes_async() {
+        let mut rng = RandJitterEntropy::new().unwrap();
+        let mut buf = [0u8; 64];
+        rng.fill_bytes_async(&mut buf).await.unwrap();
+        assert!(buf.iter().any(|&b| b != 0));
+
+        // the collector is back in place
+        assert!(rng.try_next_u64().is_ok());
+    }
+
+    #[test]
+    fn test_run_self_tests() {
+        assert_eq!(RandJitterEntropy::run_self_tests(), Ok(()));
+
+        let mut rng = RandJitterEntropy::new().unwrap();
+        assert_eq!(RandJitterEntropy::run_self_tests(), Ok(()));
+        assert!(rng.try_next_u64().is_ok());
+    }
+
+    #[test]
+    fn test_on_fips_failure() {
+        fn no_op() {}
+
+        assert_eq!(RandJitterEntropy::on_fips_failure(no_op), Ok(()));
+
+        let mut rng = RandJitterEntropy::new().unwrap();
+        assert!(rng.try_next_u64().is_ok());
+    }
+
+    #[test]
+    fn test_borrowed_try_rng_core() {
+        fn draw<R: TryRngCore>(r: &mut R) -> Result<u64, R::Error> {
+            r.try_next_u64()
+        }
+
+        let mut rng = RandJitterEntropy::new().unwrap();
+        assert!(draw(&mut &mut rng).is_ok());
+
+        let mut borrowed = &mut rng;
+        let mut buf = [0u8; 16];
+        assert!(borrowed.try_fill_bytes(&mut buf).is_ok());
+        assert!(draw(&mut borrowed).is_ok());
+        assert!(rng.try_next_u32().is_ok());
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_speed() {
+        let mut rng = RandJitterEntropy::new().unwrap();
+        let datarate = rng
+            .measure_throughput(std::time::Duration::from_secs(2), 32)
+            .unwrap()
+            / 1024.0;
+        println!("datarate: {datarate} KiB/s");
+    }
+
+    #[test]
+    fn test_bytes() {
+        let mut rng = RandJitterEntropy::new().unwrap();
+
+        for buffer_size in 0..=256 {
+            let mut buffer = vec![0u8; buffer_size];
+            assert!(rng.try_fill_bytes(&mut buffer).is_ok());
+            println!("{buffer_size}: {buffer:#04X?}");
+        }
+    }
+
+    #[cfg(feature = "openssl")]
+    #[test]
+    fn test_openssl_backend() {
+        let mut rng = RandJitterEntropy::new().unwrap();
+        assert!(rng.try_next_u64().is_ok());
+    }
+
+    #[test]
+    fn test_free() {
+        let mut rng = RandJitterEntropy::new().unwrap();
+        rng.release();
+        assert!(rng.rand_data.is_null());
+        // second release from drop must be a no-op
+        drop(rng);
+
+        let rng = RandJitterEntropy::new().unwrap();
+        rng.free();
+    }
+
+    #[test]
+    fn test_fork_detection() {
+        let mut rng = RandJitterEntropy::new().unwrap();
+        rng.pid = rng.pid.wrapping_add(1);
+
+        let mut b = [0u8; 32];
+        assert_eq!(
+            rng.try_fill_bytes(&mut b),
+            Err(JitterEntropyError::ForkDetected)
+        );
+
+        rng.reinit_after_fork().unwrap();
+        assert!(rng.try_fill_bytes(&mut b).is_ok());
+    }
+
+    #[cfg(feature = "digest")]
+    #[test]
+    fn test_read_into_hasher() {
+        use sha3::{Digest, Sha3_256};
+
+        let mut rng = RandJitterEntropy::new().unwrap();
+        let mut hasher = Sha3_256::new();
+        rng.read_into_hasher(&mut hasher, 1000).unwrap();
+
+        let digest = hasher.finalize();
+        assert_eq!(digest.len(), 32);
+        assert_ne!(digest.as_slice(), Sha3_256::digest(b"").as_slice());
+    }
+
+    #[cfg(feature = "raw-entropy")]
+    #[test]
+    fn test_read_raw_entropy() {
+        let mut rng = RandJitterEntropy::new().unwrap();
+        let mut buf = [0u8; 128];
+        assert_eq!(rng.read_raw_entropy(&mut buf), Ok(buf.len()));
+        assert_ne!(buf, [0u8; 128]);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_io_read() {
+        let mut rng = RandJitterEntropy::new().unwrap();
+        let reader: &mut dyn std::io::Read = &mut rng;
+
+        let mut buf = vec![0u8; 1024];
+        assert_eq!(reader.read(&mut buf).unwrap(), 1024);
+
+        let copied = std::io::copy(&mut reader.take(1024), &mut std::io::sink()).unwrap();
+        assert_eq!(copied, 1024);
+    }
+
+    #[test]
+    fn test_new_with_config() {
+        let config = JitterConfig {
+            osr: 5,
+            retries: 2,
+            reseed_after_bytes: Some(64),
+            ..JitterConfig::default()
+        };
+        let mut rng = RandJitterEntropy::new_with_config(config).unwrap();
+
+        for _ in 0..8 {
+            let mut b = [0u8; 32];
+            rng.try_fill_bytes(&mut b).unwrap();
+            assert!(rng.bytes_since_reseed < 64);
+        }
+        assert_eq!(rng.config, config);
+    }
+
+    #[cfg(feature = "kernel")]
+    #[test]
+    fn test_fill_bytes_after_kernel_ready() {
+        use std::time::{Duration, Instant};
+
+        let mut rng = RandJitterEntropy::new().unwrap();
+        let mut b = [0u8; 32];
+
+        let start = Instant::now();
+        rng.fill_bytes_after_kernel_ready(&mut b, Duration::from_secs(5))
+            .unwrap();
+        assert!(start.elapsed() < Duration::from_secs(5));
+
+        rng.fill_bytes_after_kernel_ready(&mut b, Duration::MAX)
+            .unwrap();
+    }
+
+    #[cfg(feature = "no-panic")]
+    #[test]
+    fn test_no_panic_api() {
+        let config = JitterConfig::default();
+        let mut rng = RandJitterEntropy::new_with_config(config).unwrap();
+        let mut b = [0u8; 64];
+        assert!(rng.try_fill_bytes(&mut b).is_ok());
+        assert!(rng.try_next_u32().is_ok());
+        assert!(rng.try_next_u64().is_ok());
+        rng.free();
+
+        let pool = JitterEntropyPool::prewarm(2).unwrap();
+        assert!(pool.acquire().unwrap().try_next_u64().is_ok());
+    }
+
+    #[test]
+    fn test_read_observer() {
+        use std::sync::{Arc, Mutex};
+
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let mut rng = RandJitterEntropy::new().unwrap();
+        let observed = Arc::clone(&seen);
+        rng.set_read_observer(move |stats| observed.lock().unwrap().push(stats));
+
+        let mut b = [0u8; 32];
+        rng.try_fill_bytes(&mut b).unwrap();
+        let _ = rng.try_next_u64().unwrap();
+
+        let seen = seen.lock().unwrap();
+        assert_eq!(seen.len(), 2);
+        assert_eq!(seen[0].bytes, 32);
+        assert_eq!(seen[1].bytes, 8);
+    }
+
+    #[test]
+    fn test_with_osr() {
+        for osr in [1, 3, 10] {
+            let mut rng = RandJitterEntropy::with_osr(osr).unwrap();
+            let mut b = [0u8; 32];
+            assert!(rng.try_fill_bytes(&mut b).is_ok());
+        }
+
+        assert!(matches!(
+            RandJitterEntropy::with_osr(0),
+            Err(JitterEntropyError::InvalidOsr)
+        ));
+    }
+
+    #[test]
+    fn test_entropy_per_bit_estimate() {
+        for osr in [1, 3, 10] {
+            let estimate = RandJitterEntropy::with_osr(osr)
+                .unwrap()
+                .entropy_per_bit_estimate();
+            assert!(estimate > 0.0 && estimate <= 1.0);
+        }
+    }
+
+    #[test]
+    fn test_library_version() {
+        assert_ne!(RandJitterEntropy::library_version(), 0);
+
+        let version = RandJitterEntropy::library_version_string();
+        assert_eq!(version.split('.').count(), 3);
+        assert!(version.starts_with("3."));
+    }
+
+    #[test]
+    fn test_non_fips() {
+        let mut rng = RandJitterEntropy::new_non_fips().unwrap();
+        assert_eq!(
+            rng.config.flags & libjitterentropy_sys::jitterentropy::JENT_FORCE_FIPS,
+            0
+        );
+        let mut b = [0u8; 64];
+        assert!(rng.try_fill_bytes(&mut b).is_ok());
+
+        // FIPS instance created while a non-FIPS instance is alive
+        let mut fips_rng = RandJitterEntropy::new().unwrap();
+        assert!(fips_rng.try_fill_bytes(&mut b).is_ok());
+    }
+
+    #[test]
+    fn test_warmup() {
+        let mut rng = RandJitterEntropy::new().unwrap();
+        rng.warmup(1000).unwrap();
+        rng.warmup(0).unwrap();
+        assert!(rng.try_next_u64().is_ok());
+
+        let mut rng = RandJitterEntropy::new().unwrap();
+        rng.warmup(1024).unwrap();
+        let mut buf = [0u8; 64];
+        assert_eq!(rng.try_fill_bytes(&mut buf), Ok(()));
+    }
+
+    #[test]
+    fn test_fill_seed() {
+        let mut rng = RandJitterEntropy::new().unwrap();
+
+        let seed: [u8; 16] = rng.fill_seed().unwrap();
+        assert_ne!(seed, [0u8; 16]);
+        let seed = rng.fill_seed::<32>().unwrap();
+        assert_ne!(seed, [0u8; 32]);
+        let seed = rng.fill_seed::<64>().unwrap();
+        assert_ne!(seed, [0u8; 64]);
+    }
+
+    #[test]
+    fn test_fill_bytes_retry() {
+        use std::sync::{
+            Arc,
+            atomic::{AtomicUsize, Ordering},
+        };
+
+        let reads = Arc::new(AtomicUsize::new(0));
+        let mut rng = RandJitterEntropy::new().unwrap();
+        let counter = Arc::clone(&reads);
+        rng.set_read_observer(move |_| {
+            counter.fetch_add(1, Ordering::Relaxed);
+        });
+
+        let mut b = [0u8; 32];
+        rng.try_fill_bytes_retry(&mut b, 5).unwrap();
+        assert_eq!(reads.load(Ordering::Relaxed), 1);
+
+        let mut calls = 0;
+        let res = retry_transient(5, || {
+            calls += 1;
+            Err(JitterEntropyError::RctPermanentFailure)
+        });
+        assert_eq!(res, Err(JitterEntropyError::RctPermanentFailure));
+        assert_eq!(calls, 1);
+
+        let mut calls = 0;
+        let res = retry_transient(5, || {
+            calls += 1;
+            Err(JitterEntropyError::AptFailed)
+        });
+        assert_eq!(res, Err(JitterEntropyError::AptFailed));
+        assert_eq!(calls, 5);
+
+        let mut calls = 0;
+        let res = retry_transient(5, || {
+            calls += 1;
+            if calls < 3 {
+                Err(JitterEntropyError::LagFailed)
+            } else {
+                Ok(())
+            }
+        });
+        assert_eq!(res, Ok(()));
+        assert_eq!(calls, 3);
+    }
+
+    #[test]
+    fn test_multi_instantiation() {
+        for _ in 0..256 {
+            let mut rng = RandJitterEntropy::new().unwrap();
+            let u = rng.try_next_u32().unwrap();
+            println!("Got {u}");
+        }
+    }
+
+    #[test]
+    fn test_multi_threading() {
+        let mut threads = vec![];
+        let mut rng = RandJitterEntropy::new().unwrap();
+        let _ = rng.try_next_u64().unwrap();
 
         println!("Got bytes (single threaded)!");
 
@@ -443,4 +2094,50 @@ mod tests {
             let _ = t.join();
         }
     }
+
+    #[test]
+    fn test_accessors() {
+        let rng = RandJitterEntropyBuilder::new()
+            .osr(6)
+            .disable_memory_access(true)
+            .build()
+            .unwrap();
+
+        assert_eq!(rng.osr(), 6);
+        assert_ne!(
+            rng.flags() & libjitterentropy_sys::jitterentropy::JENT_DISABLE_MEMORY_ACCESS,
+            0
+        );
+        assert_eq!(rng.flags(), rng.config().effective_flags());
+    }
+
+    #[test]
+    fn test_reinit() {
+        let mut rng = RandJitterEntropy::with_osr(5).unwrap();
+        let _ = rng.try_next_u64().unwrap();
+
+        let rand_data = rng.rand_data;
+        rng.reinit().unwrap();
+        assert_ne!(rng.rand_data, rand_data);
+        assert_eq!(rng.config.osr, 5);
+
+        let mut b = [0u8; 64];
+        rng.try_fill_bytes(&mut b).unwrap();
+        assert_ne!(b, [0u8; 64]);
+    }
+
+    #[test]
+    fn test_send_to_thread() {
+        let mut rng = RandJitterEntropy::new().unwrap();
+        let _ = rng.try_next_u64().unwrap();
+
+        let handle = std::thread::spawn(move || {
+            let mut b = [0u8; 64];
+            rng.try_fill_bytes(&mut b).unwrap();
+            rng
+        });
+
+        let mut rng = handle.join().unwrap();
+        assert!(rng.try_next_u32().is_ok());
+    }
 }
@@ -0,0 +1,158 @@
+use crate::{JitterEntropyError, RandJitterEntropy};
+use std::mem::ManuallyDrop;
+use std::ops::{Deref, DerefMut};
+use std::sync::Mutex;
+
+/// Pool of pre-allocated jitterentropy collectors.
+///
+/// Collector allocation runs the jitterentropy self-tests and is slow. The pool keeps
+/// collectors around and hands them out via `acquire`, returning them on guard drop.
+pub struct JitterEntropyPool {
+    collectors: Mutex<Vec<RandJitterEntropy>>,
+}
+
+/// Collector borrowed from a `JitterEntropyPool`, returned to the pool on drop.
+pub struct PooledJitter<'a> {
+    pool: &'a JitterEntropyPool,
+    rng: ManuallyDrop<RandJitterEntropy>,
+}
+
+impl JitterEntropyPool {
+    /// Create pool of `size` collectors, allocated one after another.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first error of `RandJitterEntropy::new`.
+    pub fn new(size: usize) -> Result<Self, JitterEntropyError> {
+        let collectors = (0..size)
+            .map(|_| RandJitterEntropy::new())
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(JitterEntropyPool {
+            collectors: Mutex::new(collectors),
+        })
+    }
+
+    /// Create pool of `n` collectors, allocated concurrently in `n` threads.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first error of `RandJitterEntropy::new` if any collector cannot be
+    /// allocated or `LockPoisoned` if an allocation thread panicked.
+    pub fn prewarm(n: usize) -> Result<Self, JitterEntropyError> {
+        let threads: Vec<_> = (0..n)
+            .map(|_| std::thread::spawn(RandJitterEntropy::new))
+            .collect();
+
+        let mut collectors = Vec::with_capacity(n);
+        for t in threads {
+            collectors.push(t.join().map_err(|_| JitterEntropyError::LockPoisoned)??);
+        }
+
+        Ok(JitterEntropyPool {
+            collectors: Mutex::new(collectors),
+        })
+    }
+
+    /// Take a collector from the pool, allocating a fresh one if the pool is exhausted.
+    ///
+    /// # Errors
+    ///
+    /// Returns `LockPoisoned` if the pool mutex is poisoned or any error of
+    /// `RandJitterEntropy::new` if a fresh collector has to be allocated.
+    pub fn acquire(&self) -> Result<PooledJitter<'_>, JitterEntropyError> {
+        let rng = self
+            .collectors
+            .lock()
+            .map_err(|_| JitterEntropyError::LockPoisoned)?
+            .pop();
+
+        let rng = match rng {
+            Some(rng) => rng,
+            None => RandJitterEntropy::new()?,
+        };
+
+        Ok(PooledJitter {
+            pool: self,
+            rng: ManuallyDrop::new(rng),
+        })
+    }
+
+    /// Number of idle collectors currently held by the pool.
+    #[must_use]
+    pub fn available(&self) -> usize {
+        self.collectors.lock().map_or(0, |c| c.len())
+    }
+}
+
+impl Deref for PooledJitter<'_> {
+    type Target = RandJitterEntropy;
+
+    fn deref(&self) -> &Self::Target {
+        &self.rng
+    }
+}
+
+impl DerefMut for PooledJitter<'_> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.rng
+    }
+}
+
+impl Drop for PooledJitter<'_> {
+    fn drop(&mut self) {
+        // SAFETY: `rng` is not accessed anymore after being taken here
+        let rng = unsafe { ManuallyDrop::take(&mut self.rng) };
+        if let Ok(mut collectors) = self.pool.collectors.lock() {
+            collectors.push(rng);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand_core::TryRngCore;
+
+    #[test]
+    fn test_prewarm() {
+        let pool = JitterEntropyPool::prewarm(4).unwrap();
+        assert_eq!(pool.available(), 4);
+
+        {
+            let mut rng = pool.acquire().unwrap();
+            assert_eq!(pool.available(), 3);
+            let mut b = [0u8; 32];
+            rng.try_fill_bytes(&mut b).unwrap();
+        }
+
+        assert_eq!(pool.available(), 4);
+    }
+
+    #[test]
+    fn test_pooled_vs_fresh() {
+        const ROUNDS: usize = 64;
+
+        let pool = JitterEntropyPool::new(2).unwrap();
+        assert_eq!(pool.available(), 2);
+        let mut b = [0u8; 32];
+
+        let start = std::time::Instant::now();
+        for _ in 0..ROUNDS {
+            pool.acquire().unwrap().try_fill_bytes(&mut b).unwrap();
+        }
+        let pooled = start.elapsed();
+
+        let start = std::time::Instant::now();
+        for _ in 0..ROUNDS {
+            RandJitterEntropy::new()
+                .unwrap()
+                .try_fill_bytes(&mut b)
+                .unwrap();
+        }
+        let fresh = start.elapsed();
+
+        println!("{ROUNDS} reads: pooled {pooled:?}, fresh {fresh:?}");
+        assert_eq!(pool.available(), 2);
+    }
+}
@@ -0,0 +1,96 @@
+use crate::{JitterEntropyError, RandJitterEntropy};
+use libjitterentropy_sys::safe;
+
+/// Diagnostic snapshot of a collector, see `RandJitterEntropy::entropy_estimate_report`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EntropyEstimateReport {
+    /// Oversampling rate of the collector
+    pub osr: u32,
+    /// Entropy credited to a single raw time delta, `1 / osr` bits
+    pub sample_entropy_bits: f64,
+    /// Rate of stuck time deltas, `None` as the linked library does not expose it
+    pub stuck_rate: Option<f64>,
+    /// Most recent runtime health test failure, `None` if no test tripped so far
+    pub last_health_failure: Option<JitterEntropyError>,
+    /// Total number of bytes successfully generated by this instance
+    pub bytes_generated: u64,
+}
+
+/// Current health test state of a collector, see `RandJitterEntropy::health_stats`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct HealthStats {
+    /// Repetition count test: number of consecutive stuck time deltas
+    pub rct_count: i32,
+    /// Adaptive proportion test: occurrences of the base value in the current window
+    pub apt_count: u32,
+    /// Adaptive proportion test: observations collected in the current window
+    pub apt_observations: u32,
+    /// `true` if any health test is in a failed state
+    pub failed: bool,
+}
+
+impl RandJitterEntropy {
+    /// Read the health test counters of the collector.
+    ///
+    /// Rising counters allow to log a degradation of the noise source before a health
+    /// test actually fails.
+    #[must_use]
+    pub fn health_stats(&self) -> HealthStats {
+        // the collector is only NULL after `free`, which consumes `self`
+        unsafe { safe::health_counters(self.rand_data) }
+            .map(|c| HealthStats {
+                rct_count: c.rct_count,
+                apt_count: c.apt_count,
+                apt_observations: c.apt_observations,
+                failed: c.health_failure != 0,
+            })
+            .unwrap_or_default()
+    }
+
+    /// Collect the entropy estimate, health status and output counters in one report.
+    ///
+    /// Intended to be attached to monitoring events or bug reports.
+    #[must_use]
+    pub fn entropy_estimate_report(&self) -> EntropyEstimateReport {
+        EntropyEstimateReport {
+            osr: self.config.osr,
+            sample_entropy_bits: self.entropy_per_bit_estimate(),
+            stuck_rate: None,
+            last_health_failure: self.last_health_failure,
+            bytes_generated: self.bytes_generated,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand_core::TryRngCore;
+
+    #[test]
+    fn test_entropy_estimate_report() {
+        let mut rng = RandJitterEntropy::with_osr(4).unwrap();
+        let mut buf = [0u8; 100];
+        for _ in 0..3 {
+            rng.try_fill_bytes(&mut buf).unwrap();
+        }
+
+        let report = rng.entropy_estimate_report();
+        assert_eq!(report.osr, 4);
+        assert!((report.sample_entropy_bits - 0.25).abs() < f64::EPSILON);
+        assert_eq!(report.stuck_rate, None);
+        assert_eq!(report.last_health_failure, None);
+        assert_eq!(report.bytes_generated, 300);
+    }
+
+    #[test]
+    fn test_health_stats() {
+        let mut rng = RandJitterEntropy::new().unwrap();
+        let mut buf = [0u8; 64];
+        rng.try_fill_bytes(&mut buf).unwrap();
+
+        let stats = rng.health_stats();
+        assert!(!stats.failed);
+        assert!(stats.rct_count >= 0);
+    }
+}
@@ -0,0 +1,173 @@
+use crate::{JitterEntropyError, RandJitterEntropy};
+use rand_core::{OsRng, TryRngCore};
+use zeroize::Zeroizing;
+
+/// Generator falling back to the kernel `getrandom` source after repeated permanent failures.
+///
+/// Output is read from jitterentropy as long as it is healthy. After `max_failures`
+/// consecutive permanent health test failures, the generator permanently switches to
+/// the operating system RNG instead of returning errors.
+pub struct ResilientRng {
+    jitter: Option<RandJitterEntropy>,
+    max_failures: u32,
+    failures: u32,
+}
+
+impl ResilientRng {
+    /// Create new generator switching to the kernel after `max_failures` permanent failures.
+    ///
+    /// # Errors
+    ///
+    /// Returns any error of `RandJitterEntropy::new`.
+    pub fn new(max_failures: u32) -> Result<Self, JitterEntropyError> {
+        Ok(ResilientRng {
+            jitter: Some(RandJitterEntropy::new()?),
+            max_failures,
+            failures: 0,
+        })
+    }
+
+    /// Create new generator, using the kernel right away if jitterentropy is unavailable.
+    ///
+    /// # Errors
+    ///
+    /// Returns `OsRngFailed` if jitterentropy cannot be initialized and the kernel
+    /// source fails as well.
+    pub fn new_or_fallback(max_failures: u32) -> Result<Self, JitterEntropyError> {
+        let jitter = match RandJitterEntropy::new() {
+            Ok(jitter) => Some(jitter),
+            Err(e) => {
+                warn!("jitterentropy unavailable: {e}, using kernel RNG");
+                OsRng
+                    .try_next_u32()
+                    .map_err(|_| JitterEntropyError::OsRngFailed)?;
+                None
+            }
+        };
+
+        Ok(ResilientRng {
+            jitter,
+            max_failures,
+            failures: 0,
+        })
+    }
+
+    /// Returns `true` once the generator switched to the kernel source.
+    #[must_use]
+    pub fn is_fallback(&self) -> bool {
+        self.jitter.is_none()
+    }
+
+    /// Accounts a jitterentropy failure, returns `Ok` if the kernel source takes over.
+    fn on_failure(&mut self, err: JitterEntropyError) -> Result<(), JitterEntropyError> {
+        if !err.is_permanent() {
+            return Err(err);
+        }
+
+        self.failures = self.failures.saturating_add(1);
+        if self.failures < self.max_failures {
+            return Err(err);
+        }
+
+        warn!(
+            "jitterentropy failed permanently {} times, switching to kernel RNG",
+            self.failures
+        );
+        self.jitter = None;
+        Ok(())
+    }
+}
+
+impl TryRngCore for ResilientRng {
+    type Error = JitterEntropyError;
+
+    /// Generates a random u32 value.
+    ///
+    /// The intermediate buffer is zeroized.
+    ///
+    /// # Errors
+    ///
+    /// Returns error if `try_fill_bytes` fails.
+    fn try_next_u32(&mut self) -> Result<u32, Self::Error> {
+        let mut bytes = Zeroizing::new([0u8; 4]);
+        self.try_fill_bytes(bytes.as_mut())?;
+
+        Ok(u32::from_ne_bytes(*bytes))
+    }
+
+    /// Generates a random u64 value.
+    ///
+    /// The intermediate buffer is zeroized.
+    ///
+    /// # Errors
+    ///
+    /// Returns error if `try_fill_bytes` fails.
+    fn try_next_u64(&mut self) -> Result<u64, Self::Error> {
+        let mut bytes = Zeroizing::new([0u8; 8]);
+        self.try_fill_bytes(bytes.as_mut())?;
+
+        Ok(u64::from_ne_bytes(*bytes))
+    }
+
+    /// Fills the provided buffer with random bytes.
+    ///
+    /// # Errors
+    ///
+    /// Returns error if:
+    /// - Entropy collection fails with an error not causing the switch to the kernel source
+    /// - `OsRngFailed` if the kernel source fails
+    fn try_fill_bytes(&mut self, dst: &mut [u8]) -> Result<(), Self::Error> {
+        if let Some(jitter) = &mut self.jitter {
+            match jitter.try_fill_bytes(dst) {
+                Ok(()) => {
+                    self.failures = 0;
+                    return Ok(());
+                }
+                Err(e) => self.on_failure(e)?,
+            }
+        }
+
+        OsRng
+            .try_fill_bytes(dst)
+            .map_err(|_| JitterEntropyError::OsRngFailed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resilient_fallback() {
+        let mut rng = ResilientRng::new(3).unwrap();
+        let mut b = [0u8; 32];
+        rng.try_fill_bytes(&mut b).unwrap();
+        assert!(!rng.is_fallback());
+
+        // transient failures do not count
+        assert!(rng.on_failure(JitterEntropyError::RctFailed).is_err());
+
+        for _ in 0..2 {
+            assert!(
+                rng.on_failure(JitterEntropyError::AptPermanentFailure)
+                    .is_err()
+            );
+            assert!(!rng.is_fallback());
+        }
+        assert!(
+            rng.on_failure(JitterEntropyError::AptPermanentFailure)
+                .is_ok()
+        );
+        assert!(rng.is_fallback());
+
+        rng.try_fill_bytes(&mut b).unwrap();
+    }
+
+    #[test]
+    fn test_resilient_init_fallback() {
+        crate::tests::FAIL_NEXT_ALLOC.set(true);
+        let mut rng = ResilientRng::new_or_fallback(3).unwrap();
+        assert!(rng.is_fallback());
+        assert!(rng.try_next_u64().is_ok());
+    }
+}
@@ -0,0 +1,44 @@
+use anyhow::{Result, anyhow};
+use nix::sched::{CpuSet, sched_getaffinity, sched_setaffinity};
+use nix::unistd::Pid;
+
+/// Pins the calling thread to `cpu`.
+///
+/// The CPU has to be part of the set of CPUs the process is currently allowed to run on,
+/// which only contains online CPUs.
+pub fn pin_to_cpu(cpu: usize) -> Result<()> {
+    let allowed = sched_getaffinity(Pid::from_raw(0))?;
+    if cpu >= CpuSet::count() || !allowed.is_set(cpu)? {
+        return Err(anyhow!(
+            "CPU {cpu} is not online or not allowed for this process"
+        ));
+    }
+
+    let mut cpu_set = CpuSet::new();
+    cpu_set.set(cpu)?;
+    sched_setaffinity(Pid::from_raw(0), &cpu_set)?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pin_to_cpu() {
+        std::thread::spawn(|| {
+            pin_to_cpu(0).unwrap();
+
+            let cpu_set = sched_getaffinity(Pid::from_raw(0)).unwrap();
+            assert!(cpu_set.is_set(0).unwrap());
+            for cpu in 1..CpuSet::count() {
+                assert!(!cpu_set.is_set(cpu).unwrap());
+            }
+        })
+        .join()
+        .unwrap();
+
+        assert!(pin_to_cpu(CpuSet::count()).is_err());
+    }
+}
@@ -0,0 +1,199 @@
+use anyhow::{Context, Result, anyhow};
+use clap::Args;
+use serde::Deserialize;
+use std::path::Path;
+use std::time::Duration;
+
+use crate::hash::HashAlgorithm;
+
+/// Seed interval used if neither the command line nor the config file sets one.
+const DEFAULT_SEED_INTERVAL_S: u64 = 10;
+
+/// Settings which can be given on the command line as well as in a TOML config file.
+///
+/// Values from the command line take precedence over the ones from the file.
+#[derive(Args, Deserialize, Debug, Default, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct Settings {
+    /// Seed once and exit, `--oneshot=false` overrides the config file
+    #[arg(
+        short,
+        long,
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "true"
+    )]
+    pub oneshot: Option<bool>,
+
+    /// Seconds between seeding cycles [default: 10]
+    #[arg(short, long)]
+    pub seed_interval_s: Option<u64>,
+
+    /// Reseed the kernel CRNG after every seeding cycle, `--force-crng-reseed=false`
+    /// overrides the config file
+    #[arg(
+        short,
+        long,
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "true"
+    )]
+    pub force_crng_reseed: Option<bool>,
+
+    /// Number of independent jitterentropy instances mixed into every block [default: 1]
+    #[arg(long, value_parser = clap::value_parser!(u16).range(1..))]
+    pub instances: Option<u16>,
+
+    /// Hash function conditioning the gathered entropy [default: sha3-512]
+    #[arg(long, value_enum)]
+    pub hash: Option<HashAlgorithm>,
+}
+
+impl Settings {
+    /// Loads settings from the TOML file at `path`.
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = std::fs::read_to_string(path)
+            .with_context(|| format!("failed to read config file {}", path.display()))?;
+        let settings: Settings = toml::from_str(&text)
+            .with_context(|| format!("invalid config file {}", path.display()))?;
+
+        if settings.instances == Some(0) {
+            return Err(anyhow!(
+                "invalid config file {}: instances must be at least 1",
+                path.display()
+            ));
+        }
+        Ok(settings)
+    }
+
+    /// Completes the settings with the values from `file` which are not set here.
+    pub fn or(self, file: Settings) -> Settings {
+        Settings {
+            oneshot: self.oneshot.or(file.oneshot),
+            seed_interval_s: self.seed_interval_s.or(file.seed_interval_s),
+            force_crng_reseed: self.force_crng_reseed.or(file.force_crng_reseed),
+            instances: self.instances.or(file.instances),
+            hash: self.hash.or(file.hash),
+        }
+    }
+
+    pub fn oneshot(&self) -> bool {
+        self.oneshot.unwrap_or(false)
+    }
+
+    pub fn force_crng_reseed(&self) -> bool {
+        self.force_crng_reseed.unwrap_or(false)
+    }
+
+    pub fn seed_interval(&self) -> Duration {
+        Duration::from_secs(self.seed_interval_s.unwrap_or(DEFAULT_SEED_INTERVAL_S))
+    }
+
+    pub fn instances(&self) -> u16 {
+        self.instances.unwrap_or(1)
+    }
+
+    pub fn hash(&self) -> HashAlgorithm {
+        self.hash.unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = r#"
+seed_interval_s = 30
+force_crng_reseed = true
+oneshot = false
+hash = "blake2b"
+instances = 4
+"#;
+
+    #[test]
+    fn test_load() {
+        let path = std::env::temp_dir().join(format!("jitter-rngd-{}.toml", std::process::id()));
+        std::fs::write(&path, SAMPLE).unwrap();
+        let settings = Settings::load(&path);
+        let _ = std::fs::remove_file(&path);
+
+        assert_eq!(
+            settings.unwrap(),
+            Settings {
+                oneshot: Some(false),
+                seed_interval_s: Some(30),
+                force_crng_reseed: Some(true),
+                instances: Some(4),
+                hash: Some(HashAlgorithm::Blake2b),
+            }
+        );
+    }
+
+    #[test]
+    fn test_cli_overrides_file() {
+        let file: Settings = toml::from_str(SAMPLE).unwrap();
+        let cli = Settings {
+            oneshot: Some(true),
+            seed_interval_s: Some(5),
+            force_crng_reseed: Some(false),
+            ..Settings::default()
+        };
+
+        let settings = cli.or(file);
+        assert!(settings.oneshot());
+        assert!(!settings.force_crng_reseed());
+        assert_eq!(settings.seed_interval(), Duration::from_secs(5));
+        assert_eq!(settings.instances(), 4);
+        assert_eq!(settings.hash(), HashAlgorithm::Blake2b);
+
+        let defaults = Settings::default();
+        assert!(!defaults.oneshot());
+        assert!(!defaults.force_crng_reseed());
+        assert_eq!(defaults.seed_interval(), Duration::from_secs(10));
+        assert_eq!(defaults.instances(), 1);
+        assert_eq!(defaults.hash(), HashAlgorithm::Sha3_512);
+    }
+
+    #[test]
+    fn test_cli_switches() {
+        #[derive(clap::Parser)]
+        struct Cli {
+            #[command(flatten)]
+            settings: Settings,
+        }
+
+        let parse = |args: &[&str]| {
+            <Cli as clap::Parser>::try_parse_from(["jitter-rngd"].iter().chain(args))
+                .unwrap()
+                .settings
+        };
+        assert_eq!(parse(&[]).oneshot, None);
+        assert_eq!(parse(&["--oneshot"]).oneshot, Some(true));
+        assert_eq!(parse(&["--oneshot=false"]).oneshot, Some(false));
+        assert_eq!(
+            parse(&["--force-crng-reseed=false"]).force_crng_reseed,
+            Some(false)
+        );
+    }
+
+    #[test]
+    fn test_load_errors() {
+        let missing = Path::new("/nonexistent/jitter-rngd.toml");
+        assert!(
+            Settings::load(missing)
+                .unwrap_err()
+                .to_string()
+                .contains("failed to read config file")
+        );
+
+        for invalid in ["seed_interval = 3", "instances = 0", "hash = \"md5\""] {
+            let path = std::env::temp_dir()
+                .join(format!("jitter-rngd-invalid-{}.toml", std::process::id()));
+            std::fs::write(&path, invalid).unwrap();
+            let settings = Settings::load(&path);
+            let _ = std::fs::remove_file(&path);
+
+            assert!(settings.is_err(), "{invalid} was accepted");
+        }
+    }
+}
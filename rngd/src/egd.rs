@@ -0,0 +1,120 @@
+use crate::mixer::{Mixer, RNG_STATE_SIZE_BYTE};
+use anyhow::{Result, anyhow};
+use log::{debug, error, info};
+use std::io::{Read, Write};
+use std::os::unix::net::UnixListener;
+use std::path::Path;
+use zeroize::Zeroize;
+
+/// Get entropy count, answered with a big endian `u32` in bits
+const CMD_GET_COUNT: u8 = 0x00;
+/// Non-blocking read, answered with a length byte followed by the data
+const CMD_READ_NONBLOCK: u8 = 0x01;
+/// Blocking read, answered with the requested data
+const CMD_READ_BLOCK: u8 = 0x02;
+/// Write entropy: big endian `u16` bits, length byte, data; no answer
+const CMD_WRITE: u8 = 0x03;
+/// Get PID, answered with a length byte followed by the PID as string
+const CMD_GET_PID: u8 = 0x04;
+
+/// The jitter source never depletes, always report a full mixer state.
+#[allow(clippy::cast_possible_truncation)]
+const ENTROPY_COUNT_BITS: u32 = (RNG_STATE_SIZE_BYTE * 8) as u32;
+
+/// Serves the EGD protocol on a Unix domain socket at `path`, one client at a time.
+pub fn serve(path: &Path, mixer: &mut Mixer) -> Result<()> {
+    let _ = std::fs::remove_file(path);
+    let listener = UnixListener::bind(path)?;
+    info!("Serving EGD protocol on {}", path.display());
+
+    for stream in listener.incoming() {
+        match stream {
+            Ok(stream) => {
+                if let Err(e) = handle_client(stream, mixer) {
+                    debug!("EGD client disconnected: {e}");
+                }
+            }
+            Err(e) => error!("Failed to accept EGD client: {e}"),
+        }
+    }
+
+    Ok(())
+}
+
+/// Answers EGD commands on `stream` until the client disconnects.
+pub fn handle_client<S: Read + Write>(mut stream: S, mixer: &mut Mixer) -> Result<()> {
+    loop {
+        let mut cmd = [0u8; 1];
+        if stream.read(&mut cmd)? == 0 {
+            return Ok(());
+        }
+
+        match cmd[0] {
+            CMD_GET_COUNT => stream.write_all(&ENTROPY_COUNT_BITS.to_be_bytes())?,
+            CMD_READ_NONBLOCK | CMD_READ_BLOCK => {
+                let mut len = [0u8; 1];
+                stream.read_exact(&mut len)?;
+
+                let mut data = vec![0u8; usize::from(len[0])];
+                let res = mixer.fill(&mut data).and_then(|()| {
+                    if cmd[0] == CMD_READ_NONBLOCK {
+                        stream.write_all(&len)?;
+                    }
+                    Ok(stream.write_all(&data)?)
+                });
+                data.zeroize();
+                res?;
+            }
+            CMD_WRITE => {
+                let mut header = [0u8; 3];
+                stream.read_exact(&mut header)?;
+
+                let mut data = vec![0u8; usize::from(header[2])];
+                stream.read_exact(&mut data)?;
+                mixer.absorb(&data);
+                data.zeroize();
+            }
+            CMD_GET_PID => {
+                let pid = std::process::id().to_string();
+                stream.write_all(&[u8::try_from(pid.len())?])?;
+                stream.write_all(pid.as_bytes())?;
+            }
+            c => return Err(anyhow!("unknown EGD command {c:#04x}")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand_jitterentropy::RandJitterEntropy;
+    use std::os::unix::net::UnixStream;
+
+    #[test]
+    fn test_egd_commands() {
+        let (mut client, server) = UnixStream::pair().unwrap();
+
+        let handle = std::thread::spawn(move || {
+            let mut mixer = Mixer::new(vec![RandJitterEntropy::new().unwrap()]);
+            handle_client(server, &mut mixer).unwrap();
+        });
+
+        client.write_all(&[CMD_GET_COUNT]).unwrap();
+        let mut count = [0u8; 4];
+        client.read_exact(&mut count).unwrap();
+        assert_eq!(u32::from_be_bytes(count), ENTROPY_COUNT_BITS);
+
+        client.write_all(&[CMD_READ_BLOCK, 100]).unwrap();
+        let mut data = [0u8; 100];
+        client.read_exact(&mut data).unwrap();
+        assert_ne!(data, [0u8; 100]);
+
+        client.write_all(&[CMD_READ_NONBLOCK, 16]).unwrap();
+        let mut len = [0u8; 1];
+        client.read_exact(&mut len).unwrap();
+        assert_eq!(len[0], 16);
+
+        drop(client);
+        handle.join().unwrap();
+    }
+}
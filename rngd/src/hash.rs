@@ -0,0 +1,80 @@
+use blake2::Blake2b512;
+use clap::ValueEnum;
+use serde::Deserialize;
+use sha2::Sha512;
+use sha3::{Digest, Sha3_512};
+
+use crate::mixer::{RNG_STATE_SIZE_BYTE, RandomState};
+
+/// Hash function used to condition the mixer state and output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum, Deserialize)]
+pub enum HashAlgorithm {
+    #[default]
+    #[value(name = "sha3-512")]
+    #[serde(rename = "sha3-512")]
+    Sha3_512,
+    #[value(name = "sha2-512")]
+    #[serde(rename = "sha2-512")]
+    Sha2_512,
+    #[value(name = "blake2b")]
+    #[serde(rename = "blake2b")]
+    Blake2b,
+}
+
+/// Dispatches to the hasher of the selected `HashAlgorithm`.
+///
+/// All algorithms produce `RNG_STATE_SIZE_BYTE` bytes of output.
+pub enum Hasher {
+    Sha3_512(Sha3_512),
+    Sha2_512(Sha512),
+    Blake2b(Blake2b512),
+}
+
+impl Hasher {
+    pub fn new(algorithm: HashAlgorithm) -> Self {
+        match algorithm {
+            HashAlgorithm::Sha3_512 => Self::Sha3_512(Sha3_512::new()),
+            HashAlgorithm::Sha2_512 => Self::Sha2_512(Sha512::new()),
+            HashAlgorithm::Blake2b => Self::Blake2b(Blake2b512::new()),
+        }
+    }
+
+    pub fn update(&mut self, data: impl AsRef<[u8]>) {
+        match self {
+            Self::Sha3_512(h) => h.update(data),
+            Self::Sha2_512(h) => h.update(data),
+            Self::Blake2b(h) => h.update(data),
+        }
+    }
+
+    pub fn finalize(self) -> RandomState {
+        let mut out = RandomState::new();
+        match self {
+            Self::Sha3_512(h) => out.0.copy_from_slice(&h.finalize()[..RNG_STATE_SIZE_BYTE]),
+            Self::Sha2_512(h) => out.0.copy_from_slice(&h.finalize()[..RNG_STATE_SIZE_BYTE]),
+            Self::Blake2b(h) => out.0.copy_from_slice(&h.finalize()[..RNG_STATE_SIZE_BYTE]),
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hashers_differ() {
+        let outputs: Vec<RandomState> = HashAlgorithm::value_variants()
+            .iter()
+            .map(|&algorithm| {
+                let mut hasher = Hasher::new(algorithm);
+                hasher.update("STATE");
+                hasher.finalize()
+            })
+            .collect();
+
+        assert_ne!(outputs[0].0, outputs[1].0);
+        assert_ne!(outputs[0].0, outputs[2].0);
+        assert_ne!(outputs[1].0, outputs[2].0);
+    }
+}
@@ -1,107 +1,429 @@
-use clap::Parser;
-use linux_crng_ioctl::ioctl::{add_randomness_to_kernel, force_kernel_crng_reseed};
+use clap::{Parser, Subcommand};
+use config::Settings;
+use linux_crng_ioctl::device::{CrngDevice, KernelEntropy};
+use linux_crng_ioctl::proc::entropy_avail;
 use log::{debug, error, info};
-use rand::{RngCore, TryRngCore};
+use metrics::Metrics;
+use mixer::Mixer;
+use nix::sys::signal::{SaFlags, SigAction, SigHandler, SigSet, Signal, sigaction};
+use pidfile::PidFile;
 use rand_jitterentropy::RandJitterEntropy;
-use sha3::{Digest, Sha3_512};
-use std::{process::ExitCode, time::Duration};
-use zeroize::{Zeroize, ZeroizeOnDrop};
+use sink::{Sink, SinkSpec};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::{
+    path::PathBuf,
+    process::ExitCode,
+    time::{Duration, Instant},
+};
+use watermark::Watermarks;
+
+mod affinity;
+mod config;
+mod egd;
+mod hash;
+mod metrics;
+mod mixer;
+mod pidfile;
+mod sanity;
+mod sink;
+#[cfg(feature = "systemd")]
+mod systemd;
+mod watermark;
+
+/// Pause between seeding cycles while filling up to the high watermark.
+const FILL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Set by `SIGTERM` and `SIGINT` to leave the seeding loop.
+static TERMINATE: AtomicBool = AtomicBool::new(false);
+
+extern "C" fn on_terminate(_: nix::libc::c_int) {
+    TERMINATE.store(true, Ordering::Relaxed);
+}
+
+/// Stops the seeding loop gracefully on `SIGTERM` and `SIGINT`.
+fn install_signal_handlers() -> nix::Result<()> {
+    let action = SigAction::new(
+        SigHandler::Handler(on_terminate),
+        SaFlags::empty(),
+        SigSet::empty(),
+    );
+    for signal in [Signal::SIGTERM, Signal::SIGINT] {
+        // SAFETY: the handler only stores to an atomic
+        unsafe { sigaction(signal, &action) }?;
+    }
+    Ok(())
+}
+
+/// Sleeps for `duration`, returns `false` early once termination was requested.
+fn sleep_unless_terminated(duration: Duration) -> bool {
+    let deadline = Instant::now() + duration;
+    while !TERMINATE.load(Ordering::Relaxed) {
+        let now = Instant::now();
+        if now >= deadline {
+            return true;
+        }
+        std::thread::sleep((deadline - now).min(FILL_INTERVAL));
+    }
+    false
+}
 
 #[derive(Parser, Debug)]
 #[command(version, about, long_about = None)]
 struct ToolArgs {
-    #[arg(short, long, default_value_t = false)]
-    oneshot: bool,
+    #[command(subcommand)]
+    command: Option<Command>,
 
-    #[arg(short, long, default_value_t = 10)]
-    seed_interval_s: u64,
+    /// Load settings from this TOML file, command line flags take precedence
+    #[arg(long)]
+    config: Option<PathBuf>,
 
-    #[arg(short, long, default_value_t = false)]
-    force_crng_reseed: bool,
+    #[command(flatten)]
+    settings: Settings,
+
+    /// Pin the seeding thread to this CPU
+    #[arg(long)]
+    cpu_affinity: Option<usize>,
+
+    /// Reject raw rng output failing a monobit sanity check
+    #[arg(long, default_value_t = false)]
+    sanity_check: bool,
+
+    /// Rejection threshold of the sanity check in standard deviations
+    #[arg(long, default_value_t = 5.0)]
+    sanity_threshold: f64,
+
+    /// Only seed once the kernel entropy count drops below this many bits
+    #[arg(long)]
+    low_watermark: Option<u32>,
+
+    /// Keep seeding until the kernel entropy count reaches this many bits,
+    /// defaults to the low watermark
+    #[arg(long, requires = "low_watermark")]
+    high_watermark: Option<u32>,
+
+    /// Write the PID to this file and refuse to start if another instance locked it
+    #[arg(long)]
+    pid_file: Option<PathBuf>,
+
+    /// Serve Prometheus metrics via HTTP on this address
+    #[cfg(feature = "metrics")]
+    #[arg(long)]
+    metrics_addr: Option<std::net::SocketAddr>,
+
+    /// Output sink, may be repeated: `kernel`, `unix:<path>` or `file:<path>`,
+    /// optionally followed by `,nocredit` to not claim any entropy
+    #[arg(long = "sink", default_value = "kernel")]
+    sinks: Vec<SinkSpec>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Serve the Entropy Gathering Daemon protocol instead of seeding sinks
+    Egd {
+        #[arg(long, default_value = "/run/egd.sock")]
+        socket: PathBuf,
+    },
 }
 
-const RNG_STATE_SIZE_BYTE: usize = 64;
+/// Runs one seeding cycle: derives the next block and hands it to all sinks.
+///
+/// Afterwards the kernel CRNG is reseeded via `reseed`, if given. Returns `true` if the
+/// block was written to all sinks. The outcome is accounted in `metrics`.
+fn seed_cycle(
+    mixer: &mut Mixer,
+    specs: &[SinkSpec],
+    sinks: &mut [Box<dyn Sink>],
+    reseed: Option<&impl KernelEntropy>,
+    metrics: &Metrics,
+) -> bool {
+    metrics.cycle();
 
-#[derive(Clone, Zeroize, ZeroizeOnDrop)]
-pub struct RandomState(pub [u8; RNG_STATE_SIZE_BYTE]);
+    let mut fed = false;
+    match mixer.next_block() {
+        Ok(output) => {
+            debug!("Gathered entropy and hashed to buf!");
 
-impl Default for RandomState {
-    fn default() -> Self {
-        Self::new()
+            fed = true;
+            for (spec, sink) in specs.iter().zip(sinks.iter_mut()) {
+                match spec.feed(sink.as_mut(), &output.0) {
+                    Ok(()) => metrics.injected(output.0.len()),
+                    Err(e) => {
+                        error!("Failed to write to sink {spec:?}: {e}");
+                        fed = false;
+                    }
+                }
+            }
+        }
+        Err(e) => {
+            error!("Not feeding sinks: {e}");
+            metrics.jitter_error();
+        }
     }
-}
 
-impl RandomState {
-    #[must_use]
-    pub fn new() -> Self {
-        RandomState([0; RNG_STATE_SIZE_BYTE])
+    if let Some(kernel) = reseed
+        && let Err(e) = kernel.reseed()
+    {
+        error!("Failed to reseed kernel CRNG: {e}");
     }
+
+    fed
 }
 
 fn main() -> ExitCode {
     env_logger::init();
 
-    let args = ToolArgs::parse();
+    let mut args = ToolArgs::parse();
 
     info!("Starting jitter-rngd");
 
-    let mut state = RandomState::new();
+    if let Some(path) = &args.config {
+        match Settings::load(path) {
+            Ok(file) => args.settings = std::mem::take(&mut args.settings).or(file),
+            Err(e) => {
+                error!("{e:#}");
+                return ExitCode::FAILURE;
+            }
+        }
+    }
+    let settings = &args.settings;
+
+    let _pid_file = match args.pid_file.as_deref().map(PidFile::acquire).transpose() {
+        Ok(pid_file) => pid_file,
+        Err(e) => {
+            error!("Failed to acquire PID file: {e}");
+            return ExitCode::FAILURE;
+        }
+    };
 
-    let mut rngs: Vec<Box<dyn RngCore>> = vec![Box::new(
-        match RandJitterEntropy::new() {
+    if let Some(cpu) = args.cpu_affinity {
+        if let Err(e) = affinity::pin_to_cpu(cpu) {
+            error!("Failed to pin to CPU {cpu}: {e}");
+            return ExitCode::FAILURE;
+        }
+        info!("Pinned seeding thread to CPU {cpu}");
+    }
+
+    let mut rngs = Vec::with_capacity(usize::from(settings.instances()));
+    for _ in 0..settings.instances() {
+        rngs.push(match RandJitterEntropy::new() {
             Ok(rng) => rng,
             Err(e) => {
                 error!("Failed to create jitterentropy instance: {}", e);
                 return ExitCode::FAILURE;
             }
-        }
-        .unwrap_err(),
-    )];
+        });
+    }
+    debug!("Created {} jitterentropy instances", rngs.len());
+    let mut mixer = Mixer::new(rngs).with_hash(settings.hash());
+    if args.sanity_check {
+        mixer = mixer.with_sanity_check(args.sanity_threshold);
+    }
 
-    loop {
-        let mut output = RandomState::new();
+    if let Some(Command::Egd { socket }) = &args.command {
+        return match egd::serve(socket, &mut mixer) {
+            Ok(()) => ExitCode::SUCCESS,
+            Err(e) => {
+                error!("EGD server failed: {e}");
+                ExitCode::FAILURE
+            }
+        };
+    }
 
-        let mut hasher_state = Sha3_512::new();
-        let mut hasher_output = Sha3_512::new();
+    let mut sinks: Vec<Box<dyn Sink>> = Vec::new();
+    for spec in &args.sinks {
+        match spec.open() {
+            Ok(sink) => sinks.push(sink),
+            Err(e) => {
+                error!("Failed to open sink {spec:?}: {e}");
+                return ExitCode::FAILURE;
+            }
+        }
+    }
 
-        // domain separation
-        hasher_state.update("STATE");
-        hasher_output.update("RAND0");
+    let reseed_device = if settings.force_crng_reseed() {
+        match CrngDevice::open() {
+            Ok(device) => Some(device),
+            Err(e) => {
+                error!("Failed to open kernel random device: {e}");
+                return ExitCode::FAILURE;
+            }
+        }
+    } else {
+        None
+    };
 
-        // add previous state back
-        hasher_state.update(state.0);
-        hasher_output.update(state.0);
+    let mut watermarks = args
+        .low_watermark
+        .map(|low| Watermarks::new(low, args.high_watermark.unwrap_or(low)));
 
-        // mix in different rngs
-        for rng in &mut rngs {
-            rng.fill_bytes(&mut output.0);
-            hasher_state.update(output.0);
-            hasher_output.update(output.0);
+    let metrics = Arc::new(Metrics::default());
+    #[cfg(feature = "metrics")]
+    if let Some(addr) = args.metrics_addr {
+        match metrics::serve(addr, Arc::clone(&metrics)) {
+            Ok(addr) => info!("Serving metrics on {addr}"),
+            Err(e) => {
+                error!("Failed to serve metrics on {addr}: {e}");
+                return ExitCode::FAILURE;
+            }
         }
+    }
 
-        let output_out = hasher_output.finalize();
-        let state_out = hasher_state.finalize();
+    if let Err(e) = install_signal_handlers() {
+        error!("Failed to install signal handlers: {e}");
+        return ExitCode::FAILURE;
+    }
 
-        let copy_len = &state.0.len();
-        state.0.copy_from_slice(&state_out[0..*copy_len]);
+    #[cfg(feature = "systemd")]
+    let mut notifier = systemd::Notifier::from_env(settings.seed_interval());
 
-        let copy_len = &output.0.len();
-        output.0.copy_from_slice(&output_out[0..*copy_len]);
+    // the first cycle seeds regardless of the watermarks, readiness follows it
+    let mut seeded = false;
+    loop {
+        let due = match &mut watermarks {
+            Some(_) if !seeded => true,
+            Some(marks) => match entropy_avail() {
+                Ok(avail) => marks.should_feed(avail),
+                Err(e) => {
+                    error!("Failed to read kernel entropy count: {e}");
+                    true
+                }
+            },
+            None => true,
+        };
 
-        debug!("Gathered entropy and hashed to buf!");
+        let fed = due
+            && seed_cycle(
+                &mut mixer,
+                &args.sinks,
+                &mut sinks,
+                reseed_device.as_ref(),
+                &metrics,
+            );
 
-        add_randomness_to_kernel(&output.0, u32::try_from(output.0.len() * 8).unwrap()).unwrap();
+        seeded |= fed;
 
-        if args.force_crng_reseed {
-            force_kernel_crng_reseed().unwrap();
+        #[cfg(feature = "systemd")]
+        if due {
+            notifier.cycle(fed);
+        } else {
+            notifier.idle();
         }
 
-        if args.oneshot {
+        if settings.oneshot() {
             break;
         }
 
-        std::thread::sleep(Duration::from_secs(args.seed_interval_s));
+        // failing sinks are retried at the regular interval
+        let interval = if fed && watermarks.is_some() {
+            FILL_INTERVAL
+        } else {
+            settings.seed_interval()
+        };
+        if !sleep_unless_terminated(interval) {
+            info!("Terminating jitter-rngd");
+            break;
+        }
     }
 
     ExitCode::SUCCESS
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use linux_crng_ioctl::error::CrngError;
+    use sink::KernelSink;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    #[derive(Clone, Default)]
+    struct MockKernel {
+        calls: Rc<RefCell<Vec<String>>>,
+    }
+
+    impl MockKernel {
+        fn record(&self, call: &str) -> Result<(), CrngError> {
+            self.calls.borrow_mut().push(call.to_string());
+            Ok(())
+        }
+    }
+
+    impl KernelEntropy for MockKernel {
+        fn get_ent_cnt(&self) -> Result<i32, CrngError> {
+            self.record("get_ent_cnt")?;
+            Ok(0)
+        }
+
+        fn add_to_ent_cnt(&self, _ent_cnt: i32) -> Result<(), CrngError> {
+            self.record("add_to_ent_cnt")
+        }
+
+        fn add_randomness(&self, _entropy: &[u8], _ent_bits: u32) -> Result<(), CrngError> {
+            self.record("add_randomness")
+        }
+
+        fn write_without_crediting(&self, data: &[u8]) -> Result<usize, CrngError> {
+            self.record("write_without_crediting")?;
+            Ok(data.len())
+        }
+
+        fn clear_entropy_count(&self) -> Result<(), CrngError> {
+            self.record("clear_entropy_count")
+        }
+
+        fn clear_pool(&self) -> Result<(), CrngError> {
+            self.record("clear_pool")
+        }
+
+        fn reseed(&self) -> Result<(), CrngError> {
+            self.record("reseed")
+        }
+    }
+
+    #[test]
+    fn test_seed_cycle_adds_randomness() {
+        let kernel = MockKernel::default();
+        let mut mixer = Mixer::new(vec![RandJitterEntropy::new().unwrap()]);
+        let specs = ["kernel".parse::<SinkSpec>().unwrap()];
+        let mut sinks: Vec<Box<dyn Sink>> = vec![Box::new(KernelSink::new(kernel.clone()))];
+
+        let metrics = Metrics::default();
+
+        for _ in 0..3 {
+            assert!(seed_cycle(
+                &mut mixer,
+                &specs,
+                &mut sinks,
+                Some(&kernel),
+                &metrics
+            ));
+        }
+
+        assert_eq!(
+            *kernel.calls.borrow(),
+            ["add_randomness", "reseed"].repeat(3)
+        );
+
+        let text = metrics.render();
+        assert!(text.contains("\nrngd_reseed_cycles_total 3\n"));
+        assert!(text.contains("\nrngd_bytes_injected_total 192\n"));
+    }
+
+    #[test]
+    fn test_seed_cycle_nocredit() {
+        let kernel = MockKernel::default();
+        let mut mixer = Mixer::new(vec![RandJitterEntropy::new().unwrap()]);
+        let specs = ["kernel,nocredit".parse::<SinkSpec>().unwrap()];
+        let mut sinks: Vec<Box<dyn Sink>> = vec![Box::new(KernelSink::new(kernel.clone()))];
+
+        assert!(seed_cycle(
+            &mut mixer,
+            &specs,
+            &mut sinks,
+            None::<&MockKernel>,
+            &Metrics::default()
+        ));
+        assert_eq!(*kernel.calls.borrow(), ["write_without_crediting"]);
+    }
+}
@@ -0,0 +1,140 @@
+use std::fmt::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+#[cfg(feature = "metrics")]
+use anyhow::Result;
+#[cfg(feature = "metrics")]
+use log::warn;
+#[cfg(feature = "metrics")]
+use std::io::{Read, Write as _};
+#[cfg(feature = "metrics")]
+use std::net::{SocketAddr, TcpListener, TcpStream};
+#[cfg(feature = "metrics")]
+use std::sync::Arc;
+
+/// Counters of the seeding loop, exported in Prometheus text format.
+#[derive(Debug, Default)]
+pub struct Metrics {
+    bytes_injected: AtomicU64,
+    reseed_cycles: AtomicU64,
+    jitter_errors: AtomicU64,
+}
+
+impl Metrics {
+    /// Accounts `bytes` written to a sink.
+    pub fn injected(&self, bytes: usize) {
+        self.bytes_injected
+            .fetch_add(u64::try_from(bytes).unwrap_or(u64::MAX), Ordering::Relaxed);
+    }
+
+    /// Accounts a seeding cycle.
+    pub fn cycle(&self) {
+        self.reseed_cycles.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Accounts a failure to gather entropy.
+    pub fn jitter_error(&self) {
+        self.jitter_errors.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Renders all counters in the Prometheus text exposition format.
+    #[cfg_attr(not(feature = "metrics"), allow(dead_code))]
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        for (name, help, counter) in [
+            (
+                "rngd_bytes_injected_total",
+                "Bytes written to all sinks.",
+                &self.bytes_injected,
+            ),
+            (
+                "rngd_reseed_cycles_total",
+                "Seeding cycles run.",
+                &self.reseed_cycles,
+            ),
+            (
+                "rngd_jitter_errors_total",
+                "Failures to gather entropy from the rngs.",
+                &self.jitter_errors,
+            ),
+        ] {
+            let _ = write!(
+                out,
+                "# HELP {name} {help}\n# TYPE {name} counter\n{name} {}\n",
+                counter.load(Ordering::Relaxed)
+            );
+        }
+        out
+    }
+}
+
+/// Serves `metrics` via HTTP on `addr` from a background thread.
+///
+/// Every request is answered with the rendered counters, regardless of method and path.
+/// Returns the bound address, which differs from `addr` if it used port 0.
+#[cfg(feature = "metrics")]
+pub fn serve(addr: SocketAddr, metrics: Arc<Metrics>) -> Result<SocketAddr> {
+    let listener = TcpListener::bind(addr)?;
+    let local_addr = listener.local_addr()?;
+
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            if let Err(e) = stream.and_then(|stream| respond(stream, &metrics)) {
+                warn!("Failed to serve metrics: {e}");
+            }
+        }
+    });
+
+    Ok(local_addr)
+}
+
+#[cfg(feature = "metrics")]
+fn respond(mut stream: TcpStream, metrics: &Metrics) -> std::io::Result<()> {
+    // the request itself does not matter
+    let mut request = [0u8; 1024];
+    let _ = stream.read(&mut request)?;
+
+    let body = metrics.render();
+    write!(
+        stream,
+        "HTTP/1.1 200 OK\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        let metrics = Metrics::default();
+        metrics.injected(64);
+        metrics.cycle();
+
+        let text = metrics.render();
+        assert!(text.contains("\nrngd_bytes_injected_total 64\n"));
+        assert!(text.contains("\nrngd_reseed_cycles_total 1\n"));
+        assert!(text.contains("\nrngd_jitter_errors_total 0\n"));
+    }
+
+    #[cfg(feature = "metrics")]
+    #[test]
+    fn test_serve() {
+        let metrics = Arc::new(Metrics::default());
+        metrics.cycle();
+        let addr = serve("127.0.0.1:0".parse().unwrap(), Arc::clone(&metrics)).unwrap();
+
+        let mut stream = TcpStream::connect(addr).unwrap();
+        stream.write_all(b"GET /metrics HTTP/1.1\r\n\r\n").unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+        let cycles = response
+            .lines()
+            .find_map(|line| line.strip_prefix("rngd_reseed_cycles_total "))
+            .unwrap();
+        assert_eq!(cycles.parse::<u64>().unwrap(), 1);
+    }
+}
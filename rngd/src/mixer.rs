@@ -0,0 +1,128 @@
+use crate::hash::{HashAlgorithm, Hasher};
+use crate::sanity::monobit_ok;
+use anyhow::{Result, anyhow};
+use log::warn;
+use rand::TryRngCore;
+use rand_jitterentropy::RandJitterEntropy;
+use zeroize::{Zeroize, ZeroizeOnDrop};
+
+pub const RNG_STATE_SIZE_BYTE: usize = 64;
+
+/// Number of reads of a single rng before giving up on a failing sanity check
+const SANITY_ATTEMPTS: usize = 8;
+
+#[derive(Clone, Zeroize, ZeroizeOnDrop)]
+pub struct RandomState(pub [u8; RNG_STATE_SIZE_BYTE]);
+
+impl Default for RandomState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RandomState {
+    #[must_use]
+    pub fn new() -> Self {
+        RandomState([0; RNG_STATE_SIZE_BYTE])
+    }
+}
+
+/// Mixes the output of all rngs together with a chained state into output blocks.
+pub struct Mixer {
+    state: RandomState,
+    rngs: Vec<RandJitterEntropy>,
+    sanity_threshold: Option<f64>,
+    hash: HashAlgorithm,
+}
+
+impl Mixer {
+    #[must_use]
+    pub fn new(rngs: Vec<RandJitterEntropy>) -> Self {
+        Mixer {
+            state: RandomState::new(),
+            rngs,
+            sanity_threshold: None,
+            hash: HashAlgorithm::default(),
+        }
+    }
+
+    /// Reject raw rng output failing the monobit test with `threshold_sigma`.
+    #[must_use]
+    pub fn with_sanity_check(mut self, threshold_sigma: f64) -> Self {
+        self.sanity_threshold = Some(threshold_sigma);
+        self
+    }
+
+    /// Condition state and output with `hash` instead of SHA3-512.
+    #[must_use]
+    pub fn with_hash(mut self, hash: HashAlgorithm) -> Self {
+        self.hash = hash;
+        self
+    }
+
+    /// Gathers entropy from all rngs and derives the next output block.
+    ///
+    /// Fails if an rng fails to gather entropy or its raw output repeatedly fails the
+    /// sanity check.
+    pub fn next_block(&mut self) -> Result<RandomState> {
+        let mut output = RandomState::new();
+
+        let mut hasher_state = Hasher::new(self.hash);
+        let mut hasher_output = Hasher::new(self.hash);
+
+        // domain separation
+        hasher_state.update("STATE");
+        hasher_output.update("RAND0");
+
+        // add previous state back
+        hasher_state.update(self.state.0);
+        hasher_output.update(self.state.0);
+
+        // mix in different rngs
+        for rng in &mut self.rngs {
+            let mut attempts = 0;
+            loop {
+                rng.try_fill_bytes(&mut output.0)?;
+                match self.sanity_threshold {
+                    Some(threshold) if !monobit_ok(&output.0, threshold) => {
+                        attempts += 1;
+                        warn!("Rejected rng output failing sanity check, re-collecting");
+                        if attempts == SANITY_ATTEMPTS {
+                            return Err(anyhow!(
+                                "rng output failed sanity check {SANITY_ATTEMPTS} times"
+                            ));
+                        }
+                    }
+                    _ => break,
+                }
+            }
+            hasher_state.update(output.0);
+            hasher_output.update(output.0);
+        }
+
+        self.state = hasher_state.finalize();
+
+        Ok(hasher_output.finalize())
+    }
+
+    /// Fills `dst` with consecutive output blocks.
+    pub fn fill(&mut self, dst: &mut [u8]) -> Result<()> {
+        for chunk in dst.chunks_mut(RNG_STATE_SIZE_BYTE) {
+            let block = self.next_block()?;
+            chunk.copy_from_slice(&block.0[..chunk.len()]);
+        }
+        Ok(())
+    }
+
+    /// Mixes externally supplied `data` into the state.
+    pub fn absorb(&mut self, data: &[u8]) {
+        let mut hasher_state = Hasher::new(self.hash);
+
+        // domain separation
+        hasher_state.update("INPUT");
+        hasher_state.update(self.state.0);
+        hasher_state.update(data);
+
+        self.state = hasher_state.finalize();
+    }
+}
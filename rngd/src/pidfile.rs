@@ -0,0 +1,98 @@
+use anyhow::{Result, anyhow};
+use log::info;
+use nix::errno::Errno;
+use nix::fcntl::{Flock, FlockArg};
+use std::fs::{File, OpenOptions};
+use std::io::{Read, Seek, Write};
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
+
+/// Exclusively locked PID file, removed again on drop.
+///
+/// The lock is released by the kernel when the process dies, so a PID file left over
+/// by a crashed instance is simply taken over. The lock is only valid as long as the
+/// locked file is still the one at the path, which `acquire` checks.
+pub struct PidFile {
+    path: PathBuf,
+    _lock: Flock<File>,
+}
+
+impl PidFile {
+    /// Locks the file at `path` and writes the PID of this process to it.
+    ///
+    /// Fails if another process holds the lock.
+    pub fn acquire(path: &Path) -> Result<Self> {
+        let mut lock = loop {
+            let lock = Self::lock(path)?;
+            // a previous instance may have removed the file after it was opened here,
+            // locking the orphaned file would let a new instance at the path run as well
+            let locked = lock.metadata()?;
+            let current = std::fs::metadata(path);
+            if current.is_ok_and(|c| c.dev() == locked.dev() && c.ino() == locked.ino()) {
+                break lock;
+            }
+        };
+
+        let file: &mut File = &mut lock;
+        let mut previous = String::new();
+        file.read_to_string(&mut previous)?;
+        if !previous.trim().is_empty() {
+            info!("Taking over stale PID file of process {}", previous.trim());
+        }
+
+        file.set_len(0)?;
+        file.rewind()?;
+        writeln!(file, "{}", std::process::id())?;
+
+        Ok(PidFile {
+            path: path.to_path_buf(),
+            _lock: lock,
+        })
+    }
+
+    /// Opens and locks the file at `path`, failing if it is locked already.
+    fn lock(path: &Path) -> Result<Flock<File>> {
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(path)?;
+
+        match Flock::lock(file, FlockArg::LockExclusiveNonblock) {
+            Ok(lock) => Ok(lock),
+            Err((_, Errno::EWOULDBLOCK)) => Err(anyhow!(
+                "another instance is running, {} is locked",
+                path.display()
+            )),
+            Err((_, e)) => Err(e.into()),
+        }
+    }
+}
+
+impl Drop for PidFile {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pid_file_lock() {
+        let path = std::env::temp_dir().join(format!("jitter-rngd-{}.pid", std::process::id()));
+        std::fs::write(&path, "4194304\n").unwrap();
+
+        let pid_file = PidFile::acquire(&path).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            format!("{}\n", std::process::id())
+        );
+        assert!(PidFile::acquire(&path).is_err());
+
+        drop(pid_file);
+        assert!(!path.exists());
+    }
+}
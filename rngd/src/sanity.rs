@@ -0,0 +1,30 @@
+/// Monobit test: checks the number of set bits of `data` against the expectation.
+///
+/// Returns `false` if the count of ones deviates from half of all bits by more than
+/// `threshold_sigma` standard deviations. This only catches catastrophic source
+/// failures, e.g. all-equal bytes.
+pub fn monobit_ok(data: &[u8], threshold_sigma: f64) -> bool {
+    let bits = (data.len() * 8) as f64;
+    let ones = f64::from(data.iter().map(|b| b.count_ones()).sum::<u32>());
+
+    let sigma = bits.sqrt() / 2.0;
+    (ones - bits / 2.0).abs() <= threshold_sigma * sigma
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::TryRngCore;
+    use rand_jitterentropy::RandJitterEntropy;
+
+    #[test]
+    fn test_monobit() {
+        assert!(!monobit_ok(&[0u8; 64], 5.0));
+        assert!(!monobit_ok(&[0xFFu8; 64], 5.0));
+
+        let mut rng = RandJitterEntropy::new().unwrap();
+        let mut block = [0u8; 64];
+        rng.try_fill_bytes(&mut block).unwrap();
+        assert!(monobit_ok(&block, 5.0));
+    }
+}
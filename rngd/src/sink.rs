@@ -0,0 +1,231 @@
+use anyhow::{Result, anyhow};
+use linux_crng_ioctl::device::{CrngDevice, KernelEntropy};
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::os::unix::net::UnixStream;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// Destination for the mixed output of each seeding cycle.
+pub trait Sink {
+    /// Hands `data` to the sink, claiming `ent_bits` of entropy.
+    fn write(&mut self, data: &[u8], ent_bits: u32) -> Result<()>;
+}
+
+/// Kind of sink as given on the command line.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SinkKind {
+    /// `kernel`: write data to the kernel CRNG input pool
+    Kernel,
+    /// `unix:<path>`: send length-prefixed data to a Unix domain socket
+    Unix(PathBuf),
+    /// `file:<path>`: append data to a file
+    File(PathBuf),
+}
+
+/// Sink description as given on the command line.
+///
+/// A `,nocredit` suffix hands data to the sink without claiming any entropy,
+/// e.g. `kernel,nocredit` mixes into the kernel pool without crediting it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SinkSpec {
+    pub kind: SinkKind,
+    pub credit: bool,
+}
+
+impl FromStr for SinkSpec {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (sink, credit) = match s.rsplit_once(',') {
+            Some((sink, "credit")) => (sink, true),
+            Some((sink, "nocredit")) => (sink, false),
+            _ => (s, true),
+        };
+
+        let kind = match sink.split_once(':') {
+            None if sink == "kernel" => SinkKind::Kernel,
+            Some(("unix", path)) if !path.is_empty() => SinkKind::Unix(path.into()),
+            Some(("file", path)) if !path.is_empty() => SinkKind::File(path.into()),
+            _ => {
+                return Err(anyhow!(
+                    "invalid sink '{s}', expected 'kernel', 'unix:<path>' or 'file:<path>', optionally followed by ',nocredit'"
+                ));
+            }
+        };
+
+        Ok(SinkSpec { kind, credit })
+    }
+}
+
+impl SinkSpec {
+    /// Opens the described sink.
+    pub fn open(&self) -> Result<Box<dyn Sink>> {
+        Ok(match &self.kind {
+            SinkKind::Kernel => Box::new(KernelSink::new(CrngDevice::open()?)),
+            SinkKind::Unix(path) => Box::new(UnixSocketSink::connect(path)?),
+            SinkKind::File(path) => Box::new(FileSink::open(path)?),
+        })
+    }
+
+    /// Hands `data` to `sink`, crediting full entropy only if configured to.
+    pub fn feed(&self, sink: &mut dyn Sink, data: &[u8]) -> Result<()> {
+        let ent_bits = if self.credit {
+            u32::try_from(data.len() * 8)?
+        } else {
+            0
+        };
+        sink.write(data, ent_bits)
+    }
+}
+
+/// Writes data to the kernel CRNG.
+///
+/// Credited data is added via ioctl, which requires root privileges. Uncredited data
+/// is plainly written to the device.
+pub struct KernelSink<K: KernelEntropy> {
+    device: K,
+}
+
+impl<K: KernelEntropy> KernelSink<K> {
+    pub fn new(device: K) -> Self {
+        KernelSink { device }
+    }
+}
+
+impl<K: KernelEntropy> Sink for KernelSink<K> {
+    fn write(&mut self, data: &[u8], ent_bits: u32) -> Result<()> {
+        if ent_bits == 0 {
+            let mut remaining = data;
+            while !remaining.is_empty() {
+                match self.device.write_without_crediting(remaining)? {
+                    0 => return Err(anyhow!("kernel accepted no data")),
+                    written => remaining = &remaining[written..],
+                }
+            }
+            Ok(())
+        } else {
+            Ok(self.device.add_randomness(data, ent_bits)?)
+        }
+    }
+}
+
+/// Sends each block prefixed with its length as big endian `u32` over a Unix domain socket.
+pub struct UnixSocketSink {
+    stream: UnixStream,
+}
+
+impl UnixSocketSink {
+    pub fn connect(path: &Path) -> Result<Self> {
+        Ok(UnixSocketSink {
+            stream: UnixStream::connect(path)?,
+        })
+    }
+}
+
+impl Sink for UnixSocketSink {
+    fn write(&mut self, data: &[u8], _ent_bits: u32) -> Result<()> {
+        self.stream
+            .write_all(&u32::try_from(data.len())?.to_be_bytes())?;
+        self.stream.write_all(data)?;
+        Ok(())
+    }
+}
+
+/// Appends raw data to a file.
+pub struct FileSink {
+    file: File,
+}
+
+impl FileSink {
+    pub fn open(path: &Path) -> Result<Self> {
+        Ok(FileSink {
+            file: OpenOptions::new().create(true).append(true).open(path)?,
+        })
+    }
+}
+
+impl Sink for FileSink {
+    fn write(&mut self, data: &[u8], _ent_bits: u32) -> Result<()> {
+        self.file.write_all(data)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+    use std::os::unix::net::UnixListener;
+
+    #[derive(Default)]
+    struct RecordingSink {
+        credited: Vec<u32>,
+    }
+
+    impl Sink for RecordingSink {
+        fn write(&mut self, _data: &[u8], ent_bits: u32) -> Result<()> {
+            self.credited.push(ent_bits);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_parse_sink_spec() {
+        assert_eq!(
+            "kernel".parse::<SinkSpec>().unwrap(),
+            SinkSpec {
+                kind: SinkKind::Kernel,
+                credit: true
+            }
+        );
+        assert_eq!(
+            "unix:/run/rngd.sock,nocredit".parse::<SinkSpec>().unwrap(),
+            SinkSpec {
+                kind: SinkKind::Unix("/run/rngd.sock".into()),
+                credit: false
+            }
+        );
+        assert!("unix:".parse::<SinkSpec>().is_err());
+        assert!("tcp:localhost".parse::<SinkSpec>().is_err());
+    }
+
+    #[test]
+    fn test_nocredit_sink() {
+        let mut sink = RecordingSink::default();
+
+        let spec: SinkSpec = "kernel,nocredit".parse().unwrap();
+        spec.feed(&mut sink, &[0u8; 64]).unwrap();
+
+        let spec: SinkSpec = "kernel".parse().unwrap();
+        spec.feed(&mut sink, &[0u8; 64]).unwrap();
+
+        assert_eq!(sink.credited, [0, 512]);
+    }
+
+    #[test]
+    fn test_unix_socket_sink() {
+        let path = std::env::temp_dir().join(format!("rngd-sink-{}.sock", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let listener = UnixListener::bind(&path).unwrap();
+
+        let spec = SinkSpec {
+            kind: SinkKind::Unix(path.clone()),
+            credit: false,
+        };
+        let mut sink = spec.open().unwrap();
+        let (mut stream, _) = listener.accept().unwrap();
+
+        spec.feed(sink.as_mut(), &[0xAB; 64]).unwrap();
+
+        let mut len = [0u8; 4];
+        stream.read_exact(&mut len).unwrap();
+        assert_eq!(u32::from_be_bytes(len), 64);
+
+        let mut data = [0u8; 64];
+        stream.read_exact(&mut data).unwrap();
+        assert_eq!(data, [0xAB; 64]);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
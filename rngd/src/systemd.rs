@@ -0,0 +1,61 @@
+use log::{debug, warn};
+use sd_notify::NotifyState;
+use std::time::Duration;
+
+/// Reports readiness and liveness to systemd, see `sd_notify(3)`.
+///
+/// `READY=1` is sent after the first seeding cycle which fed all sinks. If the service
+/// manager set `WATCHDOG_USEC`, every further healthy or idle cycle sends `WATCHDOG=1`,
+/// so a wedged or persistently failing jitter source gets the daemon restarted.
+pub struct Notifier {
+    ready: bool,
+    watchdog: bool,
+}
+
+impl Notifier {
+    /// Reads the watchdog settings of the environment, `interval` is the pause between cycles.
+    pub fn from_env(interval: Duration) -> Self {
+        let mut usec = 0;
+        let watchdog = sd_notify::watchdog_enabled(false, &mut usec);
+        if watchdog && interval >= Duration::from_micros(usec) {
+            warn!(
+                "Seed interval of {}s exceeds watchdog timeout of {}us",
+                interval.as_secs(),
+                usec
+            );
+        }
+
+        Notifier {
+            ready: false,
+            watchdog,
+        }
+    }
+
+    /// Notifies systemd after a seeding cycle, nothing is sent for unhealthy cycles.
+    pub fn cycle(&mut self, healthy: bool) {
+        if !healthy {
+            return;
+        }
+
+        if !self.ready {
+            self.ready = true;
+            notify(NotifyState::Ready);
+        } else if self.watchdog {
+            notify(NotifyState::Watchdog);
+        }
+    }
+
+    /// Notifies systemd after a cycle with nothing to seed, which only pings the watchdog.
+    pub fn idle(&mut self) {
+        if self.ready && self.watchdog {
+            notify(NotifyState::Watchdog);
+        }
+    }
+}
+
+fn notify(state: NotifyState) {
+    debug!("Notifying systemd: {state}");
+    if let Err(e) = sd_notify::notify(false, &[state]) {
+        warn!("Failed to notify systemd: {e}");
+    }
+}
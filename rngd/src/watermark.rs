@@ -0,0 +1,57 @@
+/// Decides when to feed the kernel based on its entropy count.
+///
+/// Feeding starts once the count drops below `low` and continues until it reaches
+/// `high`. In between, the previous decision is kept, so the pool is not churned by
+/// small fluctuations around a single threshold.
+#[derive(Debug)]
+pub struct Watermarks {
+    low: u32,
+    high: u32,
+    filling: bool,
+}
+
+impl Watermarks {
+    /// Watermarks in bits, `high` is raised to `low` if smaller.
+    pub fn new(low: u32, high: u32) -> Self {
+        Watermarks {
+            low,
+            high: high.max(low),
+            filling: false,
+        }
+    }
+
+    /// Returns `true` if entropy should be added at the current `entropy_avail`.
+    pub fn should_feed(&mut self, entropy_avail: u32) -> bool {
+        if entropy_avail < self.low {
+            self.filling = true;
+        } else if entropy_avail >= self.high {
+            self.filling = false;
+        }
+        self.filling
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_watermarks() {
+        let mut marks = Watermarks::new(128, 256);
+
+        assert!(!marks.should_feed(200));
+        assert!(marks.should_feed(100));
+        assert!(marks.should_feed(200));
+        assert!(!marks.should_feed(256));
+        assert!(!marks.should_feed(200));
+        assert!(marks.should_feed(0));
+    }
+
+    #[test]
+    fn test_watermarks_high_below_low() {
+        let mut marks = Watermarks::new(256, 128);
+
+        assert!(marks.should_feed(200));
+        assert!(!marks.should_feed(256));
+    }
+}
@@ -0,0 +1,93 @@
+use std::process::Command;
+use std::time::{Duration, Instant};
+
+/// Runs `jitter-rngd --oneshot` with a file sink and returns the written output.
+fn oneshot(name: &str, extra_args: &[&str]) -> Vec<u8> {
+    // a file sink needs no root privileges, unlike the default kernel sink
+    let path = std::env::temp_dir().join(format!("jitter-rngd-{name}-{}", std::process::id()));
+
+    let status = Command::new(env!("CARGO_BIN_EXE_jitter-rngd"))
+        .arg("--oneshot")
+        .arg("--sink")
+        .arg(format!("file:{}", path.display()))
+        .args(extra_args)
+        .status()
+        .unwrap();
+
+    let written = std::fs::read(&path);
+    let _ = std::fs::remove_file(&path);
+
+    assert!(status.success());
+    written.unwrap()
+}
+
+#[test]
+fn test_oneshot() {
+    assert!(!oneshot("oneshot", &[]).is_empty());
+}
+
+#[test]
+fn test_oneshot_hash() {
+    for hash in ["sha3-512", "sha2-512", "blake2b"] {
+        let first = oneshot(hash, &["--hash", hash]);
+        let second = oneshot(hash, &["--hash", hash]);
+
+        assert!(!first.is_empty());
+        assert_ne!(first, second);
+    }
+}
+
+#[test]
+fn test_oneshot_instances() {
+    let output = oneshot("instances", &["--instances", "3"]);
+
+    assert_eq!(output.len(), 64);
+    assert!(output.iter().any(|&b| b != 0));
+}
+
+#[test]
+fn test_pid_file_single_instance() {
+    use nix::sys::signal::{Signal, kill};
+    use nix::unistd::Pid;
+
+    let path = std::env::temp_dir().join(format!("jitter-rngd-lock-{}.pid", std::process::id()));
+    let pid_file_arg = format!("--pid-file={}", path.display());
+    let sink_arg = format!("file:{}", path.with_extension("out").display());
+
+    let mut first = Command::new(env!("CARGO_BIN_EXE_jitter-rngd"))
+        .args([
+            &pid_file_arg,
+            "--seed-interval-s",
+            "60",
+            "--sink",
+            &sink_arg,
+        ])
+        .spawn()
+        .unwrap();
+    let first_pid = first.id().to_string();
+    let deadline = Instant::now() + Duration::from_secs(30);
+    while !std::fs::read_to_string(&path).is_ok_and(|pid| pid.trim() == first_pid) {
+        if Instant::now() >= deadline || first.try_wait().unwrap().is_some() {
+            let _ = first.kill();
+            panic!("first instance did not write its PID file");
+        }
+        std::thread::sleep(Duration::from_millis(10));
+    }
+
+    let second = Command::new(env!("CARGO_BIN_EXE_jitter-rngd"))
+        .args([&pid_file_arg, "--oneshot", "--sink", &sink_arg])
+        .status()
+        .unwrap();
+
+    kill(
+        Pid::from_raw(i32::try_from(first.id()).unwrap()),
+        Signal::SIGTERM,
+    )
+    .unwrap();
+    let first = first.wait().unwrap();
+    let _ = std::fs::remove_file(path.with_extension("out"));
+
+    assert!(!second.success());
+    assert!(first.success());
+    assert!(!path.exists());
+}